@@ -1,7 +1,18 @@
-use xenon::{window::Window, widgets::RandomRectangle, rendering::WgpuRenderer, math::Size2D};
+use xenon::{
+    math::Size2D,
+    rendering::{DebugRenderer, FallbackRenderer, WgpuRenderer},
+    widgets::RandomRectangle,
+    window::Window,
+};
 
 fn main() {
     env_logger::init();
-    let window = Window::<WgpuRenderer, _>::new("WGPU Example", RandomRectangle::new(Size2D::new(800.0, 600.0)));
-    window.run();
+    let window = Window::<FallbackRenderer<WgpuRenderer, DebugRenderer>, _>::new(
+        "WGPU Example",
+        RandomRectangle::new(Size2D::new(800.0, 600.0)),
+    );
+    match window {
+        Ok(window) => window.run(),
+        Err(err) => eprintln!("Failed to create window: {err}"),
+    }
 }
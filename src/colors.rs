@@ -1,3 +1,11 @@
+use std::{fmt::Display, str::FromStr};
+
+mod adjust;
+mod css;
+mod spaces;
+pub use self::css::ParseColorError;
+pub use self::spaces::*;
+
 /// A color. Contains r, g, b, and a values.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Color {
@@ -45,49 +53,114 @@ impl Color {
     }
     /// Creates a new `Color` from the given HSLA values (note: this is not free, unlike `rgb` and `rgba`).
     pub fn hsla(h: u16, s: f32, l: f32, a: u8) -> Self {
-        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-        let x = c * (1.0 - ((h as f32 / 60.0) % 2.0 - 1.0).abs());
-        let m = l - c / 2.0;
-        let (rprime, gprime, bprime) = if h <= 60 {
-            (c, x, 0.0)
-        } else if h < 120 {
-            (x, c, 0.0)
-        } else if h < 180 {
-            (0.0, c, x)
-        } else if h < 240 {
-            (0.0, x, c)
-        } else if h < 300 {
-            (x, 0.0, c)
-        } else {
-            (c, 0.0, x)
-        };
-        Self {
-            r: ((rprime + m) * 255.0) as u8,
-            g: ((gprime + m) * 255.0) as u8,
-            b: ((bprime + m) * 255.0) as u8,
-            a,
+        Hsl {
+            hue: h as f32,
+            saturation: s,
+            lightness: l,
+            alpha: a as f32 / 255.0,
         }
+        .into()
     }
-    /// Creates a new `Color` from the given hex string.
-    pub fn from_hex(hex: &str) -> Self {
-        let mut hex = hex;
-        if hex.starts_with('#') {
-            hex = &hex[1..];
-        }
-        let r = u8::from_str_radix(&hex[0..2], 16).unwrap();
-        let g = u8::from_str_radix(&hex[2..4], 16).unwrap();
-        let b = u8::from_str_radix(&hex[4..6], 16).unwrap();
-        let a = if hex.len() == 8 {
-            u8::from_str_radix(&hex[6..8], 16).unwrap()
-        } else {
-            255
-        };
-        Self { r, g, b, a }
+    /// Creates a new `Color` from a hex string of 3, 4, 6 or 8 digits, with or without a `#`.
+    pub fn from_hex(hex: &str) -> Result<Self, ParseColorError> {
+        css::parse_hex(hex)
     }
     /// Outputs the color as a hex string.
     pub fn to_hex(&self) -> String {
         format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
     }
+    /// Converts the color to linear-light RGB with straight alpha, as used for blending.
+    pub fn to_linear(&self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.r as f32 / 255.0),
+            srgb_to_linear(self.g as f32 / 255.0),
+            srgb_to_linear(self.b as f32 / 255.0),
+            self.a as f32 / 255.0,
+        ]
+    }
+    /// Creates a new `Color` from linear-light RGB with straight alpha, clamping to the sRGB gamut.
+    pub fn from_linear([r, g, b, a]: [f32; 4]) -> Self {
+        let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Self {
+            r: channel(linear_to_srgb(r)),
+            g: channel(linear_to_srgb(g)),
+            b: channel(linear_to_srgb(b)),
+            a: channel(a),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses a CSS color: 3, 4, 6 or 8 digit hex, a named color, or an `rgb()`, `rgba()`,
+    /// `hsl()`, `hsla()` or `hwb()` function.
+    fn from_str(css: &str) -> Result<Self, Self::Err> {
+        css::parse(css)
+    }
+}
+/// Displays the color as lowercase `#rrggbbaa` hex.
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// Colors are written as hex strings, like their `Display` output.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+/// Colors can be read from CSS color strings or from maps of `r`, `g`, `b` and an optional `a`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn opaque() -> u8 {
+            255
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(
+            untagged,
+            expecting = "a CSS color string or a map of r, g, b and optionally a"
+        )]
+        enum Repr {
+            Css(String),
+            Channels {
+                r: u8,
+                g: u8,
+                b: u8,
+                #[serde(default = "opaque")]
+                a: u8,
+            },
+        }
+        match Repr::deserialize(deserializer)? {
+            // Hex without a `#` isn't CSS, but is accepted too.
+            Repr::Css(css) => Color::from_str(&css)
+                .or_else(|error| Color::from_hex(&css).map_err(|_| error))
+                .map_err(serde::de::Error::custom),
+            Repr::Channels { r, g, b, a } => Ok(Color { r, g, b, a }),
+        }
+    }
+}
+
+/// Decodes an sRGB-encoded channel in `0.0..=1.0` to linear light.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a linear-light channel in `0.0..=1.0` with the sRGB transfer function.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
 }
 impl From<u32> for Color {
     /// Creates a new `Color` from a 64-bit integer. Bit interpretation is as follows:
@@ -95,6 +168,7 @@ impl From<u32> for Color {
     /// - 8..16: Blue
     /// - 16..24: Green
     /// - 24..32: Red
+    ///
     /// This means that `0xFF000000` is 100% red.
     fn from(color: u32) -> Self {
         Self {
@@ -105,3 +179,56 @@ impl From<u32> for Color {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_round_trip() {
+        for value in 0..=255 {
+            let color = Color::rgba(value, 255 - value, value / 2, value);
+            assert_eq!(Color::from_linear(color.to_linear()), color);
+        }
+    }
+
+    #[test]
+    fn srgb_midpoint_is_darker_in_linear() {
+        let [r, ..] = Color::rgb(128, 0, 0).to_linear();
+        assert!((r - 0.2158).abs() < 0.001);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_accepts_hex_and_channels() {
+        assert_eq!(
+            serde_json::to_string(&Color::rgba(255, 128, 0, 64)).unwrap(),
+            r##""#ff800040""##
+        );
+        let read = |json: &str| serde_json::from_str::<Color>(json);
+        assert_eq!(read(r##""#ff8000""##).unwrap(), Color::rgb(255, 128, 0));
+        assert_eq!(read(r#""ff800040""#).unwrap(), Color::rgba(255, 128, 0, 64));
+        assert_eq!(
+            read(r#"{"r": 255, "g": 128, "b": 0}"#).unwrap(),
+            Color::rgb(255, 128, 0)
+        );
+        assert_eq!(
+            read(r#""rebeccapurple""#).unwrap(),
+            Color::rgb(102, 51, 153)
+        );
+        assert!(read(r##""#ff80f""##)
+            .unwrap_err()
+            .to_string()
+            .contains("3, 4, 6 or 8 digits"));
+        assert!(read("[1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let color = Color::rgba(1, 2, 254, 128);
+        assert_eq!(color.to_string(), "#0102fe80");
+        assert_eq!(color.to_string().parse(), Ok(color.clone()));
+        assert_eq!(Color::from_hex("0102fe80"), Ok(color));
+        assert!(Color::from_hex("#01").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use super::{Color, Hsl};
+
+impl Color {
+    fn adjust_hsl(&self, adjust: impl FnOnce(&mut Hsl)) -> Color {
+        let mut hsl = Hsl::from(self);
+        adjust(&mut hsl);
+        hsl.saturation = hsl.saturation.clamp(0.0, 1.0);
+        hsl.lightness = hsl.lightness.clamp(0.0, 1.0);
+        hsl.into()
+    }
+    /// Raises the color's HSL lightness by `amount`, out of 1.
+    pub fn lighten(&self, amount: f32) -> Color {
+        self.adjust_hsl(|hsl| hsl.lightness += amount)
+    }
+    /// Lowers the color's HSL lightness by `amount`, out of 1.
+    pub fn darken(&self, amount: f32) -> Color {
+        self.lighten(-amount)
+    }
+    /// Raises the color's HSL saturation by `amount`, out of 1.
+    pub fn saturate(&self, amount: f32) -> Color {
+        self.adjust_hsl(|hsl| hsl.saturation += amount)
+    }
+    /// Lowers the color's HSL saturation by `amount`, out of 1.
+    pub fn desaturate(&self, amount: f32) -> Color {
+        self.saturate(-amount)
+    }
+    /// The same color with its alpha replaced.
+    pub fn with_alpha(&self, alpha: u8) -> Color {
+        Color { a: alpha, ..*self }
+    }
+    /// The opposite color, keeping alpha.
+    pub fn invert(&self) -> Color {
+        Color::rgba(255 - self.r, 255 - self.g, 255 - self.b, self.a)
+    }
+    /// The gray with the same relative luminance, keeping alpha.
+    pub fn grayscale(&self) -> Color {
+        let luminance = self.relative_luminance();
+        Color::from_linear([luminance, luminance, luminance, self.a as f32 / 255.0])
+    }
+    /// This color drawn over `background`, blended in linear light as the renderer does.
+    pub fn over(&self, background: &Color) -> Color {
+        let [sr, sg, sb, sa] = self.to_linear();
+        let [br, bg, bb, ba] = background.to_linear();
+        let alpha = sa + ba * (1.0 - sa);
+        if alpha == 0.0 {
+            return Color::TRANSPARENT;
+        }
+        let blend = |s: f32, b: f32| (s * sa + b * ba * (1.0 - sa)) / alpha;
+        Color::from_linear([blend(sr, br), blend(sg, bg), blend(sb, bb), alpha])
+    }
+    /// The color's relative luminance as defined by WCAG, from 0 for black to 1 for white.
+    /// Alpha is ignored.
+    pub fn relative_luminance(&self) -> f32 {
+        let [r, g, b, _] = self.to_linear();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+    /// The WCAG contrast ratio between this color and `other`, from 1 to 21. Alpha is ignored,
+    /// so composite translucent colors over their background with `over` first.
+    ///
+    /// WCAG AA asks for at least 4.5 for body text and 3 for large text; AAA asks for 7 and 4.5.
+    pub fn contrast_ratio(&self, other: &Color) -> f32 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+    /// Black or white, whichever contrasts more with this color as a background.
+    pub fn readable_foreground(&self) -> Color {
+        self.most_readable(&[Color::BLACK, Color::WHITE])
+            .unwrap_or(Color::BLACK)
+    }
+    /// The one of `candidates` that contrasts most with this color as a background, or `None` if
+    /// there are none.
+    pub fn most_readable(&self, candidates: &[Color]) -> Option<Color> {
+        candidates
+            .iter()
+            .max_by(|a, b| self.contrast_ratio(a).total_cmp(&self.contrast_ratio(b)))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjusts_lightness_and_saturation() {
+        let orange = Color::rgb(255, 128, 0);
+        assert_eq!(orange.lighten(0.25), Color::rgb(255, 192, 128));
+        assert_eq!(orange.darken(0.25), Color::rgb(128, 64, 0));
+        assert_eq!(orange.darken(2.0), Color::BLACK);
+        assert_eq!(orange.desaturate(1.0), Color::rgb(128, 128, 128));
+        assert_eq!(
+            Color::rgb(191, 128, 64).saturate(1.0),
+            Color::rgb(255, 129, 0)
+        );
+        assert_eq!(orange.with_alpha(10).lighten(0.1).a, 10);
+    }
+
+    #[test]
+    fn inverts_and_grays() {
+        assert_eq!(
+            Color::rgba(255, 128, 0, 50).invert(),
+            Color::rgba(0, 127, 255, 50)
+        );
+        let gray = Color::rgb(255, 128, 0).grayscale();
+        assert!(gray.r == gray.g && gray.g == gray.b);
+        assert!(
+            (gray.relative_luminance() - Color::rgb(255, 128, 0).relative_luminance()).abs()
+                < 0.005
+        );
+    }
+
+    #[test]
+    fn composites_over_backgrounds() {
+        assert_eq!(Color::RED.over(&Color::BLUE), Color::RED);
+        assert_eq!(Color::TRANSPARENT.over(&Color::BLUE), Color::BLUE);
+        // Half red over blue mixes in linear light.
+        assert_eq!(
+            Color::RED.with_alpha(128).over(&Color::BLUE),
+            Color::rgb(188, 0, 187)
+        );
+        // Two translucent layers make a more opaque one.
+        let layered = Color::RED.with_alpha(128).over(&Color::RED.with_alpha(128));
+        assert_eq!(layered, Color::rgba(255, 0, 0, 192));
+    }
+
+    #[test]
+    fn measures_contrast() {
+        assert!((Color::WHITE.relative_luminance() - 1.0).abs() < 1e-6);
+        assert!((Color::BLACK.contrast_ratio(&Color::WHITE) - 21.0).abs() < 1e-3);
+        assert!((Color::RED.contrast_ratio(&Color::RED) - 1.0).abs() < 1e-6);
+        // #767676 is the lightest gray that passes AA on white.
+        assert!(Color::rgb(0x76, 0x76, 0x76).contrast_ratio(&Color::WHITE) >= 4.5);
+        assert!(Color::rgb(0x77, 0x77, 0x77).contrast_ratio(&Color::WHITE) < 4.5);
+    }
+
+    #[test]
+    fn picks_readable_foregrounds() {
+        assert_eq!(Color::rgb(0, 0, 128).readable_foreground(), Color::WHITE);
+        assert_eq!(Color::YELLOW.readable_foreground(), Color::BLACK);
+        let candidates = [Color::RED, Color::rgb(20, 20, 60), Color::YELLOW];
+        assert_eq!(
+            Color::WHITE.most_readable(&candidates),
+            Some(Color::rgb(20, 20, 60))
+        );
+        assert_eq!(Color::WHITE.most_readable(&[]), None);
+    }
+}
@@ -0,0 +1,467 @@
+use std::{f32::consts::TAU, fmt::Display};
+
+use super::{spaces::hsl_to_srgb, Color};
+
+/// An error parsing a CSS color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// The string was empty.
+    Empty,
+    /// Hex colors have 3, 4, 6 or 8 digits.
+    InvalidHexLength { length: usize },
+    /// A character in a hex color that isn't a hex digit.
+    InvalidHexDigit { character: char },
+    /// A word that isn't a CSS color name.
+    UnknownName(String),
+    /// A function other than `rgb`, `rgba`, `hsl`, `hsla` or `hwb`.
+    UnknownFunction(String),
+    /// A function call without its closing parenthesis.
+    Unterminated,
+    /// A function was given the wrong number of arguments.
+    WrongArgumentCount { function: String, found: usize },
+    /// An argument that isn't a number, percentage or angle the function accepts.
+    InvalidArgument { function: String, argument: String },
+}
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseColorError::Empty => write!(f, "expected a color, found an empty string"),
+            ParseColorError::InvalidHexLength { length } => {
+                write!(f, "hex colors have 3, 4, 6 or 8 digits, not {length}")
+            }
+            ParseColorError::InvalidHexDigit { character } => {
+                write!(f, "{character:?} is not a hex digit")
+            }
+            ParseColorError::UnknownName(name) => write!(f, "{name:?} is not a CSS color name"),
+            ParseColorError::UnknownFunction(function) => {
+                write!(f, "{function:?} is not a CSS color function")
+            }
+            ParseColorError::Unterminated => write!(f, "expected a closing parenthesis"),
+            ParseColorError::WrongArgumentCount { function, found } => write!(
+                f,
+                "{function}() takes 3 arguments and an optional alpha, but was given {found}"
+            ),
+            ParseColorError::InvalidArgument { function, argument } => {
+                write!(f, "invalid argument {argument:?} to {function}()")
+            }
+        }
+    }
+}
+impl std::error::Error for ParseColorError {}
+
+/// Parses 3, 4, 6 or 8 hex digits, with or without a leading `#`.
+pub(super) fn parse_hex(hex: &str) -> Result<Color, ParseColorError> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if let Some(character) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(ParseColorError::InvalidHexDigit { character });
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    match hex.len() {
+        3 | 4 => {
+            // Each digit is doubled: `#abc` is `#aabbcc`.
+            let short = |i: usize| digit(i) * 17;
+            let a = if hex.len() == 4 { short(3) } else { 255 };
+            Ok(Color::rgba(short(0), short(1), short(2), a))
+        }
+        6 | 8 => {
+            let a = if hex.len() == 8 { pair(6) } else { 255 };
+            Ok(Color::rgba(pair(0), pair(2), pair(4), a))
+        }
+        length => Err(ParseColorError::InvalidHexLength { length }),
+    }
+}
+
+/// Parses any CSS color: hex, a name, or an `rgb()`, `hsl()` or `hwb()` function.
+pub(super) fn parse(css: &str) -> Result<Color, ParseColorError> {
+    let css = css.trim();
+    if css.is_empty() {
+        return Err(ParseColorError::Empty);
+    }
+    if css.starts_with('#') {
+        return parse_hex(css);
+    }
+    let Some((function, arguments)) = css.split_once('(') else {
+        let name = css.to_ascii_lowercase();
+        return named(&name).ok_or(ParseColorError::UnknownName(css.to_owned()));
+    };
+    let function = function.trim().to_ascii_lowercase();
+    let arguments = arguments
+        .trim_end()
+        .strip_suffix(')')
+        .ok_or(ParseColorError::Unterminated)?;
+    let arguments = Arguments::split(&function, arguments)?;
+    match function.as_str() {
+        "rgb" | "rgba" => {
+            let channel = |i: usize| arguments.channel(i);
+            Ok(Color::rgba(
+                channel(0)?,
+                channel(1)?,
+                channel(2)?,
+                arguments.alpha()?,
+            ))
+        }
+        "hsl" | "hsla" => {
+            let rgb = hsl_to_srgb(
+                arguments.hue(0)?,
+                arguments.fraction(1)?,
+                arguments.fraction(2)?,
+            );
+            Ok(from_fractions(rgb, arguments.alpha()?))
+        }
+        "hwb" => {
+            let (hue, mut white, mut black) = (
+                arguments.hue(0)?,
+                arguments.fraction(1)?,
+                arguments.fraction(2)?,
+            );
+            if white + black > 1.0 {
+                let total = white + black;
+                white /= total;
+                black /= total;
+            }
+            let rgb = hsl_to_srgb(hue, 1.0, 0.5).map(|c| c * (1.0 - white - black) + white);
+            Ok(from_fractions(rgb, arguments.alpha()?))
+        }
+        _ => Err(ParseColorError::UnknownFunction(function)),
+    }
+}
+
+fn from_fractions([r, g, b]: [f32; 3], a: u8) -> Color {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color::rgba(channel(r), channel(g), channel(b), a)
+}
+
+/// The arguments of a color function, in either the comma-separated or the space-separated
+/// syntax.
+struct Arguments<'a> {
+    function: &'a str,
+    values: Vec<&'a str>,
+}
+impl<'a> Arguments<'a> {
+    fn split(function: &'a str, arguments: &'a str) -> Result<Self, ParseColorError> {
+        let values = if arguments.contains(',') {
+            arguments.split(',').map(str::trim).collect::<Vec<_>>()
+        } else {
+            let (channels, alpha) = match arguments.split_once('/') {
+                Some((channels, alpha)) => (channels, Some(alpha.trim())),
+                None => (arguments, None),
+            };
+            let mut values = channels.split_whitespace().collect::<Vec<_>>();
+            values.extend(alpha);
+            values
+        };
+        if !matches!(values.len(), 3 | 4) || values.iter().any(|value| value.is_empty()) {
+            return Err(ParseColorError::WrongArgumentCount {
+                function: function.to_owned(),
+                found: values.iter().filter(|value| !value.is_empty()).count(),
+            });
+        }
+        Ok(Self { function, values })
+    }
+    fn invalid(&self, i: usize) -> ParseColorError {
+        ParseColorError::InvalidArgument {
+            function: self.function.to_owned(),
+            argument: self.values[i].to_owned(),
+        }
+    }
+    /// A number, with `none` meaning zero.
+    fn number(&self, i: usize, value: &str) -> Result<f32, ParseColorError> {
+        if value.eq_ignore_ascii_case("none") {
+            return Ok(0.0);
+        }
+        value
+            .parse::<f32>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| self.invalid(i))
+    }
+    /// A number or percentage, where 100% is `full`.
+    fn scaled(&self, i: usize, full: f32) -> Result<f32, ParseColorError> {
+        let value = self.values[i];
+        match value.strip_suffix('%') {
+            Some(percentage) => Ok(self.number(i, percentage)? / 100.0 * full),
+            None => self.number(i, value),
+        }
+    }
+    /// An RGB channel: `0` to `255`, or a percentage.
+    fn channel(&self, i: usize) -> Result<u8, ParseColorError> {
+        Ok(self.scaled(i, 255.0)?.clamp(0.0, 255.0).round() as u8)
+    }
+    /// A saturation, lightness, whiteness or blackness: a percentage, or a number out of 100.
+    fn fraction(&self, i: usize) -> Result<f32, ParseColorError> {
+        Ok((self.scaled(i, 100.0)? / 100.0).clamp(0.0, 1.0))
+    }
+    /// A hue in degrees, from a number or an angle with a unit.
+    fn hue(&self, i: usize) -> Result<f32, ParseColorError> {
+        let value = self.values[i].to_ascii_lowercase();
+        for (unit, turn) in [("deg", 360.0), ("grad", 400.0), ("rad", TAU), ("turn", 1.0)] {
+            if let Some(number) = value.strip_suffix(unit) {
+                return Ok(self.number(i, number)? / turn * 360.0);
+            }
+        }
+        self.number(i, &value)
+    }
+    /// The optional alpha: `0` to `1`, or a percentage.
+    fn alpha(&self) -> Result<u8, ParseColorError> {
+        if self.values.len() < 4 {
+            return Ok(255);
+        }
+        Ok((self.scaled(3, 1.0)?.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+}
+
+/// The CSS color named `name`, which must be lowercase.
+fn named(name: &str) -> Option<Color> {
+    if name == "transparent" {
+        return Some(Color::TRANSPARENT);
+    }
+    let index = NAMED_COLORS
+        .binary_search_by_key(&name, |(name, _)| name)
+        .ok()?;
+    let [r, g, b] = NAMED_COLORS[index].1;
+    Some(Color::rgb(r, g, b))
+}
+
+/// The CSS named colors, sorted by name.
+const NAMED_COLORS: [(&str, [u8; 3]); 148] = [
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("rebeccapurple", [102, 51, 153]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_colors_are_sorted() {
+        assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(parse("#abc"), Ok(Color::rgb(0xaa, 0xbb, 0xcc)));
+        assert_eq!(parse("#abcd"), Ok(Color::rgba(0xaa, 0xbb, 0xcc, 0xdd)));
+        assert_eq!(parse("#A0B1C2"), Ok(Color::rgb(0xa0, 0xb1, 0xc2)));
+        assert_eq!(parse("#a0b1c2d3"), Ok(Color::rgba(0xa0, 0xb1, 0xc2, 0xd3)));
+        assert_eq!(
+            parse("#abcde"),
+            Err(ParseColorError::InvalidHexLength { length: 5 })
+        );
+        assert_eq!(
+            parse("#abg"),
+            Err(ParseColorError::InvalidHexDigit { character: 'g' })
+        );
+        // Multi-byte characters are rejected rather than sliced through.
+        assert_eq!(
+            parse_hex("aé1"),
+            Err(ParseColorError::InvalidHexDigit { character: 'é' })
+        );
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(parse("RebeccaPurple"), Ok(Color::rgb(102, 51, 153)));
+        assert_eq!(parse("green"), Ok(Color::rgb(0, 128, 0)));
+        assert_eq!(parse(" transparent "), Ok(Color::TRANSPARENT));
+        assert_eq!(
+            parse("reddish"),
+            Err(ParseColorError::UnknownName("reddish".to_owned()))
+        );
+        assert_eq!(parse("  "), Err(ParseColorError::Empty));
+    }
+
+    #[test]
+    fn parses_functions() {
+        let orange = Color::rgb(255, 128, 0);
+        for css in [
+            "rgb(255, 128, 0)",
+            "rgb(100% 50.2% 0%)",
+            "RGBA(255 128 0 / 1)",
+            "hsl(30.12, 100%, 50%)",
+            "hsl(0.08367turn 100 50)",
+            "hwb(30.12 0% 0%)",
+        ] {
+            assert_eq!(parse(css), Ok(orange.clone()), "{css}");
+        }
+        assert_eq!(
+            parse("rgba(255, 0, 0, 0.5)"),
+            Ok(Color::rgba(255, 0, 0, 128))
+        );
+        assert_eq!(
+            parse("hsl(120 100% 25% / 50%)"),
+            Ok(Color::rgba(0, 128, 0, 128))
+        );
+        assert_eq!(parse("hwb(0 60% 60%)"), Ok(Color::rgb(128, 128, 128)));
+        assert_eq!(parse("hsl(3.14159rad 0% 100%)"), Ok(Color::WHITE));
+    }
+
+    #[test]
+    fn reports_function_errors() {
+        assert_eq!(
+            parse("rgb(1, 2)"),
+            Err(ParseColorError::WrongArgumentCount {
+                function: "rgb".to_owned(),
+                found: 2
+            })
+        );
+        assert_eq!(
+            parse("hsl(red 50% 50%)"),
+            Err(ParseColorError::InvalidArgument {
+                function: "hsl".to_owned(),
+                argument: "red".to_owned()
+            })
+        );
+        assert_eq!(parse("rgb(1 2 3"), Err(ParseColorError::Unterminated));
+        assert_eq!(
+            parse("lab(50 0 0)"),
+            Err(ParseColorError::UnknownFunction("lab".to_owned()))
+        );
+    }
+}
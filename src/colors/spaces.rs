@@ -0,0 +1,769 @@
+use super::{linear_to_srgb, srgb_to_linear, Color};
+
+/// A color in linear-light RGB with straight alpha, as used for blending.
+///
+/// Unlike `Color`, channels are `f32`, so gradients and animations don't band, and they may go
+/// past `1.0` for extended-range (HDR) output. Paths, `Canvas` fills and `ColorVertex` take it
+/// directly; colors are only clamped where they're converted to `Color` or written to a surface
+/// that can't hold them.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// A color as hue, saturation and lightness.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsl {
+    /// The hue in degrees, `0.0..360.0`.
+    pub hue: f32,
+    pub saturation: f32,
+    pub lightness: f32,
+    pub alpha: f32,
+}
+
+/// A color as hue, saturation and value.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsv {
+    /// The hue in degrees, `0.0..360.0`.
+    pub hue: f32,
+    pub saturation: f32,
+    pub value: f32,
+    pub alpha: f32,
+}
+
+/// A color in CIELAB, relative to the D65 white point. `l` runs from 0 to 100.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// A color in OKLab, a perceptually uniform space. `l` runs from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+/// A color in OKLCH: OKLab as lightness, chroma and hue.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklch {
+    pub l: f32,
+    pub chroma: f32,
+    /// The hue in degrees, `0.0..360.0`.
+    pub hue: f32,
+    pub alpha: f32,
+}
+
+/// A space to interpolate colors in, for `Color::mix`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorSpace {
+    /// Gamma-encoded sRGB, as CSS gradients traditionally use. Mixes tend to look dark.
+    Srgb,
+    /// Linear-light RGB, which mixes like light does.
+    LinearRgb,
+    Hsl,
+    Hsv,
+    Lab,
+    /// Perceptually even steps in lightness, without hue shifts.
+    #[default]
+    Oklab,
+    /// Like `Oklab`, but travels around the hue wheel, keeping mixes saturated.
+    Oklch,
+}
+
+/// The gamma-encoded sRGB channels of `color`, which may go past `0.0..=1.0`.
+fn srgb(color: LinearRgb) -> [f32; 3] {
+    [color.r, color.g, color.b].map(linear_to_srgb)
+}
+
+fn from_srgb(rgb: [f32; 3], alpha: f32) -> LinearRgb {
+    let [r, g, b] = rgb.map(srgb_to_linear);
+    LinearRgb::new(r, g, b, alpha)
+}
+
+/// `Color`'s channels are already gamma-encoded, so sRGB-based spaces use them directly rather
+/// than rounding through linear light.
+fn color_srgb(color: &Color) -> [f32; 3] {
+    [color.r, color.g, color.b].map(|c| c as f32 / 255.0)
+}
+
+fn color_from_srgb([r, g, b]: [f32; 3], alpha: f32) -> Color {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color::rgba(channel(r), channel(g), channel(b), channel(alpha))
+}
+
+/// The hue in degrees and the largest and smallest channels of sRGB `rgb`.
+fn hue_and_range([r, g, b]: [f32; 3]) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    (hue, max, min)
+}
+
+/// Converts a hue in degrees, saturation and lightness to sRGB in `0.0..=1.0`.
+pub(super) fn hsl_to_srgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+    let hue = hue.rem_euclid(360.0) / 30.0;
+    let a = saturation * lightness.min(1.0 - lightness);
+    [0.0, 8.0, 4.0].map(|n| {
+        let k = (n + hue) % 12.0;
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    })
+}
+
+fn hsv_to_srgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    [5.0, 3.0, 1.0].map(|n| {
+        let k = (n + hue) % 6.0;
+        value - value * saturation * k.min(4.0 - k).clamp(0.0, 1.0)
+    })
+}
+
+fn multiply(matrix: [[f32; 3]; 3], vector: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+/// Linear sRGB to CIE XYZ, both relative to D65.
+const RGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+const XYZ_TO_RGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+const D65: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+
+/// Linear sRGB to OKLab's cone responses, and back.
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [0.412_221_46, 0.536_332_55, 0.051_445_995],
+    [0.211_903_5, 0.680_699_5, 0.107_396_96],
+    [0.088_302_46, 0.281_718_85, 0.629_978_7],
+];
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [4.076_741_7, -3.307_711_6, 0.230_969_94],
+    [-1.268_438, 2.609_757_4, -0.341_319_38],
+    [-0.004_196_086_3, -0.703_418_6, 1.707_614_7],
+];
+/// OKLab's nonlinear cone responses to L, a and b, and back.
+const LMS_TO_OKLAB: [[f32; 3]; 3] = [
+    [0.210_454_26, 0.793_617_8, -0.004_072_047],
+    [1.977_998_5, -2.428_592_2, 0.450_593_7],
+    [0.025_904_037, 0.782_771_77, -0.808_675_77],
+];
+const OKLAB_TO_LMS: [[f32; 3]; 3] = [
+    [1.0, 0.396_337_78, 0.215_803_76],
+    [1.0, -0.105_561_346, -0.063_854_17],
+    [1.0, -0.089_484_18, -1.291_485_5],
+];
+
+impl LinearRgb {
+    pub const fn new(r: f32, g: f32, b: f32, alpha: f32) -> Self {
+        Self { r, g, b, alpha }
+    }
+    /// Decodes gamma-encoded sRGB channels, such as those from a color picker, without rounding
+    /// them to 8 bits first. Channels above `1.0` stay extended.
+    pub fn from_srgb(r: f32, g: f32, b: f32, alpha: f32) -> Self {
+        Self::new(
+            srgb_to_linear(r),
+            srgb_to_linear(g),
+            srgb_to_linear(b),
+            alpha,
+        )
+    }
+    /// The color with its RGB channels multiplied by `factor`, keeping alpha. Factors above `1.0`
+    /// make HDR highlights.
+    pub fn scaled(self, factor: f32) -> Self {
+        Self::new(
+            self.r * factor,
+            self.g * factor,
+            self.b * factor,
+            self.alpha,
+        )
+    }
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.alpha]
+    }
+}
+impl From<[f32; 4]> for LinearRgb {
+    fn from([r, g, b, alpha]: [f32; 4]) -> Self {
+        Self { r, g, b, alpha }
+    }
+}
+impl From<LinearRgb> for [f32; 4] {
+    fn from(color: LinearRgb) -> Self {
+        color.to_array()
+    }
+}
+impl From<&Color> for LinearRgb {
+    fn from(color: &Color) -> Self {
+        let [r, g, b, alpha] = color.to_linear();
+        Self { r, g, b, alpha }
+    }
+}
+impl From<LinearRgb> for Color {
+    fn from(LinearRgb { r, g, b, alpha }: LinearRgb) -> Self {
+        Color::from_linear([r, g, b, alpha])
+    }
+}
+
+impl Hsl {
+    fn from_srgb(rgb: [f32; 3], alpha: f32) -> Self {
+        let (hue, max, min) = hue_and_range(rgb);
+        let lightness = (max + min) / 2.0;
+        // Out-of-gamut colors can be lighter than white, leaving no room for saturation.
+        let room = 1.0 - (2.0 * lightness - 1.0).abs();
+        let saturation = if max == min || room <= 0.0 {
+            0.0
+        } else {
+            (max - min) / room
+        };
+        Self {
+            hue,
+            saturation,
+            lightness,
+            alpha,
+        }
+    }
+    fn to_srgb(self) -> [f32; 3] {
+        hsl_to_srgb(self.hue, self.saturation, self.lightness)
+    }
+}
+impl From<LinearRgb> for Hsl {
+    fn from(color: LinearRgb) -> Self {
+        Self::from_srgb(srgb(color), color.alpha)
+    }
+}
+impl From<Hsl> for LinearRgb {
+    fn from(hsl: Hsl) -> Self {
+        from_srgb(hsl.to_srgb(), hsl.alpha)
+    }
+}
+impl From<&Color> for Hsl {
+    fn from(color: &Color) -> Self {
+        Self::from_srgb(color_srgb(color), color.a as f32 / 255.0)
+    }
+}
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Self {
+        color_from_srgb(hsl.to_srgb(), hsl.alpha)
+    }
+}
+
+impl Hsv {
+    fn from_srgb(rgb: [f32; 3], alpha: f32) -> Self {
+        let (hue, max, min) = hue_and_range(rgb);
+        Self {
+            hue,
+            saturation: if max == 0.0 { 0.0 } else { (max - min) / max },
+            value: max,
+            alpha,
+        }
+    }
+    fn to_srgb(self) -> [f32; 3] {
+        hsv_to_srgb(self.hue, self.saturation, self.value)
+    }
+}
+impl From<LinearRgb> for Hsv {
+    fn from(color: LinearRgb) -> Self {
+        Self::from_srgb(srgb(color), color.alpha)
+    }
+}
+impl From<Hsv> for LinearRgb {
+    fn from(hsv: Hsv) -> Self {
+        from_srgb(hsv.to_srgb(), hsv.alpha)
+    }
+}
+impl From<&Color> for Hsv {
+    fn from(color: &Color) -> Self {
+        Self::from_srgb(color_srgb(color), color.a as f32 / 255.0)
+    }
+}
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Self {
+        color_from_srgb(hsv.to_srgb(), hsv.alpha)
+    }
+}
+
+impl From<LinearRgb> for Lab {
+    fn from(LinearRgb { r, g, b, alpha }: LinearRgb) -> Self {
+        let xyz = multiply(RGB_TO_XYZ, [r, g, b]);
+        let [fx, fy, fz] = [0, 1, 2].map(|i| {
+            let t = xyz[i] / D65[i];
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        });
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+            alpha,
+        }
+    }
+}
+impl From<Lab> for LinearRgb {
+    fn from(lab: Lab) -> Self {
+        let fy = (lab.l + 16.0) / 116.0;
+        let f = [fy + lab.a / 500.0, fy, fy - lab.b / 200.0];
+        let xyz = [0, 1, 2].map(|i| {
+            let t = if f[i].powi(3) > 216.0 / 24389.0 {
+                f[i].powi(3)
+            } else {
+                (116.0 * f[i] - 16.0) / (24389.0 / 27.0)
+            };
+            t * D65[i]
+        });
+        let [r, g, b] = multiply(XYZ_TO_RGB, xyz);
+        Self::new(r, g, b, lab.alpha)
+    }
+}
+
+impl From<LinearRgb> for Oklab {
+    fn from(LinearRgb { r, g, b, alpha }: LinearRgb) -> Self {
+        let lms = multiply(RGB_TO_LMS, [r, g, b]).map(f32::cbrt);
+        let [l, a, b] = multiply(LMS_TO_OKLAB, lms);
+        Self { l, a, b, alpha }
+    }
+}
+impl From<Oklab> for LinearRgb {
+    fn from(Oklab { l, a, b, alpha }: Oklab) -> Self {
+        let lms = multiply(OKLAB_TO_LMS, [l, a, b]).map(|c| c * c * c);
+        let [r, g, b] = multiply(LMS_TO_RGB, lms);
+        Self::new(r, g, b, alpha)
+    }
+}
+
+impl From<Oklab> for Oklch {
+    fn from(Oklab { l, a, b, alpha }: Oklab) -> Self {
+        Self {
+            l,
+            chroma: a.hypot(b),
+            hue: b.atan2(a).to_degrees().rem_euclid(360.0),
+            alpha,
+        }
+    }
+}
+impl From<Oklch> for Oklab {
+    fn from(lch: Oklch) -> Self {
+        let (sin, cos) = lch.hue.to_radians().sin_cos();
+        Self {
+            l: lch.l,
+            a: lch.chroma * cos,
+            b: lch.chroma * sin,
+            alpha: lch.alpha,
+        }
+    }
+}
+impl From<LinearRgb> for Oklch {
+    fn from(color: LinearRgb) -> Self {
+        Oklab::from(color).into()
+    }
+}
+impl From<Oklch> for LinearRgb {
+    fn from(lch: Oklch) -> Self {
+        Oklab::from(lch).into()
+    }
+}
+
+macro_rules! from_owned_color {
+    ($($space:ty),*) => {$(
+        impl From<Color> for $space {
+            fn from(color: Color) -> Self {
+                Self::from(&color)
+            }
+        }
+    )*};
+}
+from_owned_color!(LinearRgb, Hsl, Hsv, Lab, Oklab, Oklch);
+
+/// Converts to and from `Color` through `LinearRgb`, rounding to 8 bits only at the end.
+macro_rules! via_linear_rgb {
+    ($($space:ty),*) => {$(
+        impl From<&Color> for $space {
+            fn from(color: &Color) -> Self {
+                LinearRgb::from(color).into()
+            }
+        }
+        impl From<$space> for Color {
+            fn from(color: $space) -> Self {
+                LinearRgb::from(color).into()
+            }
+        }
+    )*};
+}
+via_linear_rgb!(Lab, Oklab, Oklch);
+
+/// Converts between two spaces through `LinearRgb`, without clamping.
+macro_rules! between_spaces {
+    ($($from:ty => $to:ty),* $(,)?) => {$(
+        impl From<$from> for $to {
+            fn from(color: $from) -> Self {
+                LinearRgb::from(color).into()
+            }
+        }
+        impl From<$to> for $from {
+            fn from(color: $to) -> Self {
+                LinearRgb::from(color).into()
+            }
+        }
+    )*};
+}
+between_spaces!(
+    Hsl => Hsv,
+    Hsl => Lab,
+    Hsl => Oklab,
+    Hsl => Oklch,
+    Hsv => Lab,
+    Hsv => Oklab,
+    Hsv => Oklch,
+    Lab => Oklab,
+    Lab => Oklch,
+);
+
+impl ColorSpace {
+    /// The color's components in this space, then its alpha.
+    fn components(self, color: LinearRgb) -> [f32; 4] {
+        match self {
+            ColorSpace::Srgb => {
+                let [r, g, b] = srgb(color);
+                [r, g, b, color.alpha]
+            }
+            ColorSpace::LinearRgb => color.to_array(),
+            ColorSpace::Hsl => {
+                let Hsl {
+                    hue,
+                    saturation,
+                    lightness,
+                    alpha,
+                } = color.into();
+                [hue, saturation, lightness, alpha]
+            }
+            ColorSpace::Hsv => {
+                let Hsv {
+                    hue,
+                    saturation,
+                    value,
+                    alpha,
+                } = color.into();
+                [hue, saturation, value, alpha]
+            }
+            ColorSpace::Lab => {
+                let Lab { l, a, b, alpha } = color.into();
+                [l, a, b, alpha]
+            }
+            ColorSpace::Oklab => {
+                let Oklab { l, a, b, alpha } = color.into();
+                [l, a, b, alpha]
+            }
+            ColorSpace::Oklch => {
+                let Oklch {
+                    l,
+                    chroma,
+                    hue,
+                    alpha,
+                } = color.into();
+                [hue, chroma, l, alpha]
+            }
+        }
+    }
+    /// Like `components`, but reads sRGB-based spaces straight from `color`'s 8-bit channels.
+    fn color_components(self, color: &Color) -> [f32; 4] {
+        match self {
+            ColorSpace::Srgb => {
+                let [r, g, b] = color_srgb(color);
+                [r, g, b, color.a as f32 / 255.0]
+            }
+            ColorSpace::Hsl => {
+                let Hsl {
+                    hue,
+                    saturation,
+                    lightness,
+                    alpha,
+                } = color.into();
+                [hue, saturation, lightness, alpha]
+            }
+            ColorSpace::Hsv => {
+                let Hsv {
+                    hue,
+                    saturation,
+                    value,
+                    alpha,
+                } = color.into();
+                [hue, saturation, value, alpha]
+            }
+            _ => self.components(color.into()),
+        }
+    }
+    /// Like `color`, but writes sRGB-based spaces straight to 8-bit channels.
+    fn to_color(self, [c0, c1, c2, alpha]: [f32; 4]) -> Color {
+        match self {
+            ColorSpace::Srgb => color_from_srgb([c0, c1, c2], alpha),
+            ColorSpace::Hsl => color_from_srgb(hsl_to_srgb(c0, c1, c2), alpha),
+            ColorSpace::Hsv => color_from_srgb(hsv_to_srgb(c0, c1, c2), alpha),
+            _ => self.color([c0, c1, c2, alpha]).into(),
+        }
+    }
+    fn color(self, [c0, c1, c2, alpha]: [f32; 4]) -> LinearRgb {
+        match self {
+            ColorSpace::Srgb => from_srgb([c0, c1, c2], alpha),
+            ColorSpace::LinearRgb => LinearRgb::new(c0, c1, c2, alpha),
+            ColorSpace::Hsl => Hsl {
+                hue: c0,
+                saturation: c1,
+                lightness: c2,
+                alpha,
+            }
+            .into(),
+            ColorSpace::Hsv => Hsv {
+                hue: c0,
+                saturation: c1,
+                value: c2,
+                alpha,
+            }
+            .into(),
+            ColorSpace::Lab => Lab {
+                l: c0,
+                a: c1,
+                b: c2,
+                alpha,
+            }
+            .into(),
+            ColorSpace::Oklab => Oklab {
+                l: c0,
+                a: c1,
+                b: c2,
+                alpha,
+            }
+            .into(),
+            ColorSpace::Oklch => Oklch {
+                l: c2,
+                chroma: c1,
+                hue: c0,
+                alpha,
+            }
+            .into(),
+        }
+    }
+    /// Whether the first component is a hue, with the second saying how colorful it is.
+    fn is_polar(self) -> bool {
+        matches!(self, ColorSpace::Hsl | ColorSpace::Hsv | ColorSpace::Oklch)
+    }
+    /// Interpolates between two colors' components in this space.
+    fn mix(self, mut from: [f32; 4], mut to: [f32; 4], t: f32) -> [f32; 4] {
+        let alpha = from[3] + (to[3] - from[3]) * t;
+        let premultiplied = if self.is_polar() { 1..3 } else { 0..3 };
+        if self.is_polar() {
+            // Gray has no hue of its own.
+            const GRAY: f32 = 1e-4;
+            if from[1] < GRAY {
+                from[0] = to[0];
+            } else if to[1] < GRAY {
+                to[0] = from[0];
+            }
+            let difference = (to[0] - from[0] + 180.0).rem_euclid(360.0) - 180.0;
+            to[0] = from[0] + difference;
+        }
+        let mut mixed = [0.0; 4];
+        for i in 0..3 {
+            mixed[i] = if premultiplied.contains(&i) {
+                let (from, to) = (from[i] * from[3], to[i] * to[3]);
+                let value = from + (to - from) * t;
+                if alpha > 0.0 {
+                    value / alpha
+                } else {
+                    value
+                }
+            } else {
+                from[i] + (to[i] - from[i]) * t
+            };
+        }
+        mixed[3] = alpha;
+        mixed
+    }
+}
+
+impl LinearRgb {
+    /// Interpolates from this color to `other` in `space`, like `Color::mix`, without clamping to
+    /// 8 bits.
+    pub fn mix(self, other: LinearRgb, t: f32, space: ColorSpace) -> LinearRgb {
+        space.color(space.mix(space.components(self), space.components(other), t))
+    }
+}
+
+impl Color {
+    /// Interpolates from this color to `other` in `space`, where `t` of 0 is this color and 1 is
+    /// `other`.
+    ///
+    /// Alpha is premultiplied while mixing, so fading to a transparent color doesn't darken.
+    /// Polar spaces take the shorter way around the hue wheel, and keep the other color's hue when
+    /// one of them is gray.
+    pub fn mix(&self, other: &Color, t: f32, space: ColorSpace) -> Color {
+        space.to_color(space.mix(
+            space.color_components(self),
+            space.color_components(other),
+            t,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [Color; 6] = [
+        Color::RED,
+        Color::WHITE,
+        Color::BLACK,
+        Color::rgba(255, 128, 0, 200),
+        Color::rgb(12, 200, 180),
+        Color::rgb(90, 40, 160),
+    ];
+
+    #[test]
+    fn conversions_round_trip() {
+        for color in SAMPLES {
+            assert_eq!(Color::from(LinearRgb::from(&color)), color);
+            assert_eq!(Color::from(Hsl::from(&color)), color);
+            assert_eq!(Color::from(Hsv::from(&color)), color);
+            assert_eq!(Color::from(Lab::from(&color)), color);
+            assert_eq!(Color::from(Oklab::from(&color)), color);
+            assert_eq!(Color::from(Oklch::from(&color)), color);
+        }
+    }
+
+    #[test]
+    fn matches_reference_values() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        let hsl = Hsl::from(Color::rgb(255, 128, 0));
+        assert!(close(hsl.hue, 30.118) && close(hsl.saturation, 1.0) && close(hsl.lightness, 0.5));
+        let hsv = Hsv::from(Color::rgb(0, 128, 128));
+        assert!(close(hsv.hue, 180.0) && close(hsv.saturation, 1.0) && close(hsv.value, 0.502));
+        let lab = Lab::from(Color::WHITE);
+        assert!(close(lab.l, 100.0) && lab.a.abs() < 0.01 && lab.b.abs() < 0.01);
+        let lab = Lab::from(Color::RED);
+        assert!((lab.l - 53.24).abs() < 0.05 && (lab.a - 80.09).abs() < 0.05);
+        let oklab = Oklab::from(Color::RED);
+        assert!(close(oklab.l, 0.628) && close(oklab.a, 0.2249) && close(oklab.b, 0.1258));
+        let oklch = Oklch::from(Color::BLUE);
+        assert!(close(oklch.l, 0.452) && (oklch.hue - 264.05).abs() < 0.05);
+    }
+
+    #[test]
+    fn hsl_constructors_match_hsl_conversion() {
+        for h in (0..360).step_by(15) {
+            let hsl = Hsl {
+                hue: h as f32,
+                saturation: 0.7,
+                lightness: 0.4,
+                alpha: 1.0,
+            };
+            assert_eq!(Color::hsl(h, 0.7, 0.4), Color::from(hsl));
+        }
+        assert_eq!(Color::hsl(60, 1.0, 0.5), Color::YELLOW);
+        assert_eq!(Color::hsla(0, 1.0, 0.5, 128), Color::rgba(255, 0, 0, 128));
+    }
+
+    #[test]
+    fn hsl_saturation_stays_finite_out_of_gamut() {
+        let hsl = Hsl::from(LinearRgb::new(4.0, 2.0, 2.0, 1.0));
+        assert_eq!(hsl.saturation, 0.0);
+        let hsl = Hsl::from(LinearRgb::new(-0.5, -1.0, -1.0, 1.0));
+        assert_eq!(hsl.saturation, 0.0);
+    }
+
+    #[test]
+    fn mixes_in_each_space() {
+        let (red, blue) = (Color::RED, Color::BLUE);
+        assert_eq!(red.mix(&blue, 0.0, ColorSpace::Oklab), red);
+        assert_eq!(red.mix(&blue, 1.0, ColorSpace::Oklch), blue);
+        assert_eq!(
+            red.mix(&blue, 0.5, ColorSpace::Srgb),
+            Color::rgb(128, 0, 128)
+        );
+        // Linear light mixes brighter than gamma-encoded sRGB.
+        assert_eq!(
+            red.mix(&blue, 0.5, ColorSpace::LinearRgb),
+            Color::rgb(188, 0, 188)
+        );
+        // Red to blue goes through magenta, the shorter way around the hue wheel.
+        let hue = Hsl::from(red.mix(&blue, 0.5, ColorSpace::Hsl)).hue;
+        assert!((hue - 300.0).abs() < 1.0, "{hue}");
+        // Mixing with gray keeps the other color's hue.
+        let pink = Color::WHITE.mix(&red, 0.5, ColorSpace::Oklch);
+        assert!((Oklch::from(&pink).hue - Oklch::from(&red).hue).abs() < 2.0);
+        assert!(Lab::from(red.mix(&Color::BLACK, 0.5, ColorSpace::Lab)).l > 20.0);
+    }
+
+    #[test]
+    fn linear_colors_keep_precision_and_range() {
+        let color = LinearRgb::from_srgb(0.5, 0.25, 1.0, 1.0);
+        assert!((color.r - 0.214_041).abs() < 1e-5);
+        assert_eq!(color.to_array(), <[f32; 4]>::from(color));
+        let bright = LinearRgb::from(Color::WHITE).scaled(4.0);
+        assert_eq!(bright.to_array(), [4.0, 4.0, 4.0, 1.0]);
+        // Only the conversion to 8-bit colors clamps.
+        assert_eq!(Color::from(bright), Color::WHITE);
+        assert!(LinearRgb::from_srgb(1.5, 0.0, 0.0, 1.0).r > 1.0);
+    }
+
+    #[test]
+    fn conversions_between_spaces_keep_extended_values() {
+        let bright = LinearRgb::new(2.0, 0.5, -0.1, 0.75);
+        let close = |color: LinearRgb| {
+            color
+                .to_array()
+                .iter()
+                .zip(bright.to_array())
+                .all(|(a, b)| (a - b).abs() < 1e-3)
+        };
+        assert!(close(Lab::from(Oklch::from(bright)).into()));
+        assert!(close(Hsv::from(Hsl::from(bright)).into()));
+        assert!(close(Oklab::from(Lab::from(Hsv::from(bright))).into()));
+        // Out-of-gamut colors keep their chroma instead of being clamped to sRGB.
+        let vivid = Oklch {
+            l: 0.7,
+            chroma: 0.4,
+            hue: 150.0,
+            alpha: 1.0,
+        };
+        assert!((Oklch::from(Lab::from(vivid)).chroma - 0.4).abs() < 1e-3);
+        let mixed = bright.mix(LinearRgb::new(4.0, 0.5, 0.0, 0.75), 0.5, ColorSpace::Oklab);
+        assert!(mixed.r > 2.0);
+    }
+
+    #[test]
+    fn mixing_with_transparent_does_not_darken() {
+        let faded = Color::RED.mix(&Color::TRANSPARENT, 0.5, ColorSpace::Srgb);
+        assert_eq!(faded, Color::rgba(255, 0, 0, 128));
+        let faded = Color::RED.mix(&Color::TRANSPARENT, 0.5, ColorSpace::Oklab);
+        assert_eq!(faded, Color::rgba(255, 0, 0, 128));
+    }
+}
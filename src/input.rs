@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 pub use winit::event::{MouseButton, VirtualKeyCode};
 
-use crate::math::{Box2D, Point2D};
+use crate::{
+    math::{Box2D, Point2D},
+    theme::Theme,
+};
 
 pub enum KeyState {
     Pressed,
@@ -10,18 +13,14 @@ pub enum KeyState {
     None,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub enum MouseState {
     Clicked,
     Down,
     Released,
+    #[default]
     None,
 }
-impl Default for MouseState {
-    fn default() -> Self {
-        MouseState::None
-    }
-}
 
 #[derive(Default)]
 pub struct Input {
@@ -34,6 +33,7 @@ pub struct Input {
     right_mouse_state: MouseState,
     mouse_buffer: Vec<(MouseState, MouseButton)>,
     bounds: Box2D,
+    theme: Theme,
 }
 impl Input {
     /// Same as `Self::default()`
@@ -46,6 +46,13 @@ impl Input {
     pub(crate) fn set_bounds(&mut self, bounds: Box2D) {
         self.bounds = bounds;
     }
+    /// The window's current theme.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+    pub(crate) fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
     /// Returns `true` if the key is currently being pressed.
     pub fn is_down(&self, key: VirtualKeyCode) -> bool {
         matches!(
@@ -4,10 +4,80 @@ pub mod rendering;
 pub mod window;
 pub mod math {
     pub use euclid::*;
-    pub type Point2D = euclid::Point2D<f32, UnknownUnit>;
-    pub type Box2D = euclid::Box2D<f32, UnknownUnit>;
-    pub type Size2D = euclid::Size2D<f32, UnknownUnit>;
-    pub type USize2D = euclid::Size2D<u32, UnknownUnit>;
+
+    /// Logical pixels, which widgets, paths and input are measured in.
+    ///
+    /// A logical pixel covers `scale_factor` physical pixels.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Logical;
+    /// Physical pixels, which the window surface is measured in.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Physical;
+
+    pub type Point2D = euclid::Point2D<f32, Logical>;
+    pub type Vector2D = euclid::Vector2D<f32, Logical>;
+    pub type Box2D = euclid::Box2D<f32, Logical>;
+    pub type Size2D = euclid::Size2D<f32, Logical>;
+    pub type USize2D = euclid::Size2D<u32, Logical>;
+    pub type Angle = euclid::Angle<f32>;
+    pub type Transform2D = euclid::Transform2D<f32, Logical, Logical>;
+    pub type PhysicalPoint2D = euclid::Point2D<f32, Physical>;
+    pub type PhysicalSize2D = euclid::Size2D<u32, Physical>;
+    /// The number of physical pixels per logical pixel.
+    pub type ScaleFactor = euclid::Scale<f32, Logical, Physical>;
+
+    /// `size` in physical pixels, rounded to the nearest pixel.
+    pub fn to_physical(size: Size2D, scale_factor: ScaleFactor) -> PhysicalSize2D {
+        (size * scale_factor).round().to_u32()
+    }
+    /// `size` in logical pixels. This isn't rounded, so `to_physical` gives back the same size.
+    pub fn to_logical(size: PhysicalSize2D, scale_factor: ScaleFactor) -> Size2D {
+        size.to_f32() / scale_factor
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn sizes_round_to_physical_pixels() {
+            let double = ScaleFactor::new(2.0);
+            assert_eq!(
+                to_physical(Size2D::new(100.0, 50.25), double),
+                PhysicalSize2D::new(200, 101)
+            );
+            assert_eq!(
+                to_logical(PhysicalSize2D::new(201, 100), double),
+                Size2D::new(100.5, 50.0)
+            );
+            let one_and_a_half = ScaleFactor::new(1.5);
+            assert_eq!(
+                to_physical(Size2D::new(101.0, 100.0), one_and_a_half),
+                PhysicalSize2D::new(152, 150)
+            );
+            assert_eq!(
+                to_physical(Size2D::new(100.3, 100.4), one_and_a_half),
+                PhysicalSize2D::new(150, 151)
+            );
+        }
+
+        #[test]
+        fn physical_sizes_survive_a_round_trip() {
+            // Otherwise laying the root out at the window's size would resize the window.
+            for scale_factor in [2.0, 1.5] {
+                let scale_factor = ScaleFactor::new(scale_factor);
+                for pixels in 1..2000 {
+                    let size = PhysicalSize2D::new(pixels, pixels + 1);
+                    assert_eq!(
+                        to_physical(to_logical(size, scale_factor), scale_factor),
+                        size
+                    );
+                }
+            }
+        }
+    }
 }
 pub mod path;
+pub mod profiler;
+pub mod theme;
 pub mod widgets;
@@ -1,8 +1,27 @@
-use lyon::path::path::BuilderWithAttributes;
+use lyon::math::Point;
 
-use crate::{colors::Color, math::Point2D, rendering::Image};
+use crate::{
+    colors::{Color, LinearRgb},
+    math::{Angle, Box2D, Point2D, Vector2D},
+    rendering::Image,
+};
 
-pub use lyon::lyon_tessellation::{StrokeOptions, LineCap, LineJoin};
+mod boolean;
+mod outline;
+mod query;
+mod segments;
+#[cfg(feature = "serde")]
+mod serialization;
+mod svg;
+mod transform;
+pub use self::boolean::*;
+pub use self::query::*;
+use self::segments::{Core, Segment};
+pub use self::svg::SvgPathError;
+pub use self::transform::*;
+pub use lyon::geom::ArcFlags;
+pub use lyon::lyon_tessellation::{FillRule, LineCap, LineJoin, StrokeOptions, TessellationError};
+pub use lyon::path::builder::BorderRadii;
 
 #[repr(transparent)]
 pub struct ColorPath(pub(crate) lyon::path::Path);
@@ -11,6 +30,8 @@ impl ColorPath {
         ColorPathBuilder::new()
     }
 }
+// Canvas can't draw these yet; textures need registering with the renderer first.
+#[allow(dead_code)]
 pub struct TexturePath(pub(crate) lyon::path::Path, pub(crate) Image);
 impl TexturePath {
     pub fn build(image: Image) -> TexturePathBuilder {
@@ -18,113 +39,520 @@ impl TexturePath {
     }
 }
 
+/// Builds a `ColorPath`. Colors are stored per vertex in linear light, so they are interpolated
+/// correctly along the path, and HDR colors keep their extended range.
 pub struct ColorPathBuilder {
-    builder: BuilderWithAttributes,
-    color: Color,
+    core: Core,
+    color: LinearRgb,
+}
+impl Default for ColorPathBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 impl ColorPathBuilder {
     pub fn new() -> Self {
         Self {
-            builder: BuilderWithAttributes::new(4),
-            color: Color::BLACK,
+            core: Core::new(4),
+            color: Color::BLACK.into(),
         }
     }
-    pub fn set_color(&mut self, color: Color) {
-        self.color = color;
-    }
-    pub fn begin(mut self, point: Point2D) -> Self {
-        self.builder.begin(
-            point,
-            &[
-                self.color.r as f32 / 255.0,
-                self.color.g as f32 / 255.0,
-                self.color.b as f32 / 255.0,
-                self.color.a as f32 / 255.0,
-            ],
-        );
-        self
+    /// Sets the color of the following endpoints, either a `Color` or an HDR `LinearRgb`.
+    pub fn set_color(&mut self, color: impl Into<LinearRgb>) {
+        self.color = color.into();
     }
-    pub fn end(mut self, close: bool) -> Self {
-        self.builder.end(close);
+    fn push(mut self, segments: impl IntoIterator<Item = Segment>) -> Self {
+        let attributes = self.color.to_array();
+        for segment in segments {
+            self.core.push(segment, &attributes);
+        }
         self
     }
-    pub fn line_to(mut self, point: Point2D) -> Self {
-        self.builder.line_to(
-            point,
-            &[
-                self.color.r as f32 / 255.0,
-                self.color.g as f32 / 255.0,
-                self.color.b as f32 / 255.0,
-                self.color.a as f32 / 255.0,
-            ],
-        );
-        self
+    pub fn begin(self, point: Point2D) -> Self {
+        self.push([Segment::Begin(point.to_untyped())])
+    }
+    pub fn end(self, close: bool) -> Self {
+        self.push([Segment::End { close }])
+    }
+    pub fn line_to(self, point: Point2D) -> Self {
+        self.push([Segment::Line {
+            to: point.to_untyped(),
+            t: 1.0,
+        }])
+    }
+    pub fn quadratic_bezier_to(self, ctrl: Point2D, point: Point2D) -> Self {
+        self.push([Segment::Quadratic {
+            ctrl: ctrl.to_untyped(),
+            to: point.to_untyped(),
+            t: 1.0,
+        }])
     }
-    pub fn quadratic_bezier_to(mut self, ctrl: Point2D, point: Point2D) -> Self {
-        self.builder.quadratic_bezier_to(
-            ctrl,
-            point,
-            &[
-                self.color.r as f32 / 255.0,
-                self.color.g as f32 / 255.0,
-                self.color.b as f32 / 255.0,
-                self.color.a as f32 / 255.0,
-            ],
+    pub fn cubic_bezier_to(self, ctrl1: Point2D, ctrl2: Point2D, point: Point2D) -> Self {
+        self.push([Segment::Cubic {
+            ctrl1: ctrl1.to_untyped(),
+            ctrl2: ctrl2.to_untyped(),
+            to: point.to_untyped(),
+            t: 1.0,
+        }])
+    }
+    /// Begins a subpath `by` away from the current point.
+    pub fn relative_begin(self, by: Vector2D) -> Self {
+        let at = self.current() + by;
+        self.begin(at)
+    }
+    pub fn relative_line_to(self, by: Vector2D) -> Self {
+        let to = self.current() + by;
+        self.line_to(to)
+    }
+    pub fn relative_quadratic_bezier_to(self, ctrl: Vector2D, by: Vector2D) -> Self {
+        let current = self.current();
+        self.quadratic_bezier_to(current + ctrl, current + by)
+    }
+    pub fn relative_cubic_bezier_to(self, ctrl1: Vector2D, ctrl2: Vector2D, by: Vector2D) -> Self {
+        let current = self.current();
+        self.cubic_bezier_to(current + ctrl1, current + ctrl2, current + by)
+    }
+    /// Continues with an arc around `center`, starting from the current point.
+    pub fn arc(
+        self,
+        center: Point2D,
+        radii: Vector2D,
+        sweep_angle: Angle,
+        x_rotation: Angle,
+    ) -> Self {
+        let segments = segments::arc(
+            &self.core.cursor,
+            center.to_untyped(),
+            radii.to_untyped(),
+            sweep_angle,
+            x_rotation,
         );
-        self
+        self.push(segments)
     }
-    pub fn cubic_bezier_to(mut self, ctrl1: Point2D, ctrl2: Point2D, point: Point2D) -> Self {
-        self.builder.cubic_bezier_to(
-            ctrl1,
-            ctrl2,
-            point,
-            &[
-                self.color.r as f32 / 255.0,
-                self.color.g as f32 / 255.0,
-                self.color.b as f32 / 255.0,
-                self.color.a as f32 / 255.0,
-            ],
+    /// Continues with an elliptical arc to `point`, like SVG's `A` command.
+    pub fn arc_to(
+        self,
+        radii: Vector2D,
+        x_rotation: Angle,
+        flags: ArcFlags,
+        point: Point2D,
+    ) -> Self {
+        let segments = segments::svg_arc(
+            &self.core.cursor,
+            radii.to_untyped(),
+            x_rotation,
+            flags,
+            point.to_untyped(),
         );
-        self
+        self.push(segments)
+    }
+    pub fn relative_arc_to(
+        self,
+        radii: Vector2D,
+        x_rotation: Angle,
+        flags: ArcFlags,
+        by: Vector2D,
+    ) -> Self {
+        let to = self.current() + by;
+        self.arc_to(radii, x_rotation, flags, to)
+    }
+    /// Adds a closed subpath around `rect`, ending any subpath in progress.
+    pub fn add_rect(self, rect: Box2D) -> Self {
+        self.push(segments::rect(&rect.to_untyped()))
+    }
+    pub fn add_rounded_rect(self, rect: Box2D, radii: BorderRadii) -> Self {
+        self.push(segments::rounded_rect(&rect.to_untyped(), &radii))
+    }
+    pub fn add_circle(self, center: Point2D, radius: f32) -> Self {
+        self.add_ellipse(center, Vector2D::new(radius, radius), Angle::zero())
+    }
+    pub fn add_ellipse(self, center: Point2D, radii: Vector2D, x_rotation: Angle) -> Self {
+        self.push(segments::ellipse(
+            center.to_untyped(),
+            radii.to_untyped(),
+            x_rotation,
+        ))
+    }
+    /// Adds a subpath through `points`, ending any subpath in progress.
+    pub fn add_polygon(self, points: &[Point2D], closed: bool) -> Self {
+        let points = points
+            .iter()
+            .map(|point| point.to_untyped())
+            .collect::<Vec<_>>();
+        self.push(segments::polygon(&points, closed))
+    }
+    /// Appends SVG path data, such as `"M10 10 L20 20 Z"`, in the current color.
+    pub fn svg(self, data: &str) -> Result<Self, SvgPathError> {
+        let segments = svg::parse(data, self.core.cursor)?;
+        Ok(self.push(segments))
+    }
+    fn current(&self) -> Point2D {
+        Point2D::from_untyped(self.core.cursor.current)
     }
     pub fn build(self) -> ColorPath {
-        ColorPath(self.builder.build())
+        ColorPath(self.core.build())
     }
 }
 
+/// Builds a `TexturePath`, with texture coordinates given per point.
+///
+/// Arcs interpolate texture coordinates from the current point's to the end point's. Shapes and
+/// SVG paths map their bounding box onto a rectangle of texture coordinates.
 pub struct TexturePathBuilder {
-    builder: BuilderWithAttributes,
+    core: Core,
     texture: Image,
+    uv: Point2D,
 }
 impl TexturePathBuilder {
     pub fn new(texture: Image) -> Self {
         Self {
-            builder: BuilderWithAttributes::new(4),
+            core: Core::new(2),
             texture,
+            uv: Point2D::zero(),
         }
     }
-    pub fn begin(mut self, point: Point2D, uv: Point2D) -> Self {
-        self.builder.begin(point, &[uv.x, uv.y]);
+    fn push(
+        mut self,
+        segments: impl IntoIterator<Item = Segment>,
+        uv_at: impl Fn(Point, f32) -> Point2D,
+    ) -> Self {
+        for segment in segments {
+            let uv = match segment {
+                Segment::Begin(at) => uv_at(at, 0.0),
+                Segment::Line { to, t }
+                | Segment::Quadratic { to, t, .. }
+                | Segment::Cubic { to, t, .. } => uv_at(to, t),
+                Segment::End { .. } => self.uv,
+            };
+            self.core.push(segment, &[uv.x, uv.y]);
+            self.uv = uv;
+        }
         self
     }
-    pub fn end(mut self, close: bool) -> Self {
-        self.builder.end(close);
-        self
+    /// Pushes segments that run from the current texture coordinates to `uv`.
+    fn push_towards(self, segments: impl IntoIterator<Item = Segment>, uv: Point2D) -> Self {
+        let start = self.uv;
+        self.push(segments, |_, t| start.lerp(uv, t))
     }
-    pub fn line_to(mut self, point: Point2D, uv: Point2D) -> Self {
-        self.builder.line_to(point, &[uv.x, uv.y]);
-        self
+    /// Pushes a shape, mapping its bounding box onto `uv`.
+    fn push_mapped(self, segments: Vec<Segment>, uv: Box2D) -> Self {
+        let bounds = Box2D::from_untyped(&segments::bounds(self.core.cursor.current, &segments));
+        let scale = uv
+            .size()
+            .to_vector()
+            .component_div(bounds.size().to_vector().max(Vector2D::splat(f32::EPSILON)));
+        self.push(segments, |point, _| {
+            uv.min + (Point2D::from_untyped(point) - bounds.min).component_mul(scale)
+        })
     }
-    pub fn quadratic_bezier_to(mut self, ctrl: Point2D, point: Point2D, uv: Point2D) -> Self {
-        self.builder.quadratic_bezier_to(ctrl, point, &[uv.x, uv.y]);
-        self
+    pub fn begin(self, point: Point2D, uv: Point2D) -> Self {
+        self.push([Segment::Begin(point.to_untyped())], |_, _| uv)
     }
-    pub fn cubic_bezier_to(mut self, ctrl1: Point2D, ctrl2: Point2D, point: Point2D, uv: Point2D) -> Self {
-        self.builder
-            .cubic_bezier_to(ctrl1, ctrl2, point, &[uv.x, uv.y]);
-        self
+    pub fn end(self, close: bool) -> Self {
+        self.push([Segment::End { close }], |_, _| unreachable!())
+    }
+    pub fn line_to(self, point: Point2D, uv: Point2D) -> Self {
+        self.push(
+            [Segment::Line {
+                to: point.to_untyped(),
+                t: 1.0,
+            }],
+            |_, _| uv,
+        )
+    }
+    pub fn quadratic_bezier_to(self, ctrl: Point2D, point: Point2D, uv: Point2D) -> Self {
+        self.push(
+            [Segment::Quadratic {
+                ctrl: ctrl.to_untyped(),
+                to: point.to_untyped(),
+                t: 1.0,
+            }],
+            |_, _| uv,
+        )
+    }
+    pub fn cubic_bezier_to(
+        self,
+        ctrl1: Point2D,
+        ctrl2: Point2D,
+        point: Point2D,
+        uv: Point2D,
+    ) -> Self {
+        self.push(
+            [Segment::Cubic {
+                ctrl1: ctrl1.to_untyped(),
+                ctrl2: ctrl2.to_untyped(),
+                to: point.to_untyped(),
+                t: 1.0,
+            }],
+            |_, _| uv,
+        )
+    }
+    /// Begins a subpath `by` away from the current point.
+    pub fn relative_begin(self, by: Vector2D, uv: Point2D) -> Self {
+        let at = self.current() + by;
+        self.begin(at, uv)
+    }
+    pub fn relative_line_to(self, by: Vector2D, uv: Point2D) -> Self {
+        let to = self.current() + by;
+        self.line_to(to, uv)
+    }
+    pub fn relative_quadratic_bezier_to(self, ctrl: Vector2D, by: Vector2D, uv: Point2D) -> Self {
+        let current = self.current();
+        self.quadratic_bezier_to(current + ctrl, current + by, uv)
+    }
+    pub fn relative_cubic_bezier_to(
+        self,
+        ctrl1: Vector2D,
+        ctrl2: Vector2D,
+        by: Vector2D,
+        uv: Point2D,
+    ) -> Self {
+        let current = self.current();
+        self.cubic_bezier_to(current + ctrl1, current + ctrl2, current + by, uv)
+    }
+    /// Continues with an arc around `center`, starting from the current point and ending at `uv`.
+    pub fn arc(
+        self,
+        center: Point2D,
+        radii: Vector2D,
+        sweep_angle: Angle,
+        x_rotation: Angle,
+        uv: Point2D,
+    ) -> Self {
+        let segments = segments::arc(
+            &self.core.cursor,
+            center.to_untyped(),
+            radii.to_untyped(),
+            sweep_angle,
+            x_rotation,
+        );
+        self.push_towards(segments, uv)
+    }
+    /// Continues with an elliptical arc to `point`, like SVG's `A` command.
+    pub fn arc_to(
+        self,
+        radii: Vector2D,
+        x_rotation: Angle,
+        flags: ArcFlags,
+        point: Point2D,
+        uv: Point2D,
+    ) -> Self {
+        let segments = segments::svg_arc(
+            &self.core.cursor,
+            radii.to_untyped(),
+            x_rotation,
+            flags,
+            point.to_untyped(),
+        );
+        self.push_towards(segments, uv)
+    }
+    pub fn relative_arc_to(
+        self,
+        radii: Vector2D,
+        x_rotation: Angle,
+        flags: ArcFlags,
+        by: Vector2D,
+        uv: Point2D,
+    ) -> Self {
+        let to = self.current() + by;
+        self.arc_to(radii, x_rotation, flags, to, uv)
+    }
+    /// Adds a closed subpath around `rect`, textured with the `uv` rectangle of the texture.
+    pub fn add_rect(self, rect: Box2D, uv: Box2D) -> Self {
+        self.push_mapped(segments::rect(&rect.to_untyped()), uv)
+    }
+    pub fn add_rounded_rect(self, rect: Box2D, radii: BorderRadii, uv: Box2D) -> Self {
+        self.push_mapped(segments::rounded_rect(&rect.to_untyped(), &radii), uv)
+    }
+    pub fn add_circle(self, center: Point2D, radius: f32, uv: Box2D) -> Self {
+        self.add_ellipse(center, Vector2D::new(radius, radius), Angle::zero(), uv)
+    }
+    pub fn add_ellipse(
+        self,
+        center: Point2D,
+        radii: Vector2D,
+        x_rotation: Angle,
+        uv: Box2D,
+    ) -> Self {
+        self.push_mapped(
+            segments::ellipse(center.to_untyped(), radii.to_untyped(), x_rotation),
+            uv,
+        )
+    }
+    pub fn add_polygon(self, points: &[Point2D], closed: bool, uv: Box2D) -> Self {
+        let points = points
+            .iter()
+            .map(|point| point.to_untyped())
+            .collect::<Vec<_>>();
+        self.push_mapped(segments::polygon(&points, closed), uv)
+    }
+    /// Appends SVG path data, mapping its bounding box onto the `uv` rectangle of the texture.
+    pub fn svg(self, data: &str, uv: Box2D) -> Result<Self, SvgPathError> {
+        let segments = svg::parse(data, self.core.cursor)?;
+        Ok(self.push_mapped(segments, uv))
+    }
+    fn current(&self) -> Point2D {
+        Point2D::from_untyped(self.core.cursor.current)
     }
     pub fn build(self) -> TexturePath {
-        TexturePath(self.builder.build(), self.texture)
+        TexturePath(self.core.build(), self.texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.05
+    }
+
+    #[test]
+    fn shapes() {
+        let circle = ColorPath::build()
+            .add_circle(Point2D::new(10.0, 10.0), 5.0)
+            .build();
+        let bounds = circle.bounding_box();
+        assert!((bounds.min - Point2D::new(5.0, 5.0)).length() < 0.01);
+        assert!((bounds.max - Point2D::new(15.0, 15.0)).length() < 0.01);
+        assert!(approx_eq(circle.length(), 10.0 * PI));
+
+        let rect = Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(20.0, 10.0));
+        let rounded = ColorPath::build()
+            .add_rounded_rect(rect, BorderRadii::new(4.0))
+            .build();
+        assert!(rounded.contains_point(Point2D::new(10.0, 5.0), FillRule::NonZero));
+        assert!(!rounded.contains_point(Point2D::new(0.5, 0.5), FillRule::NonZero));
+
+        let triangle = ColorPath::build()
+            .add_polygon(
+                &[
+                    Point2D::new(0.0, 0.0),
+                    Point2D::new(10.0, 0.0),
+                    Point2D::new(0.0, 10.0),
+                ],
+                true,
+            )
+            .build();
+        assert!(approx_eq(triangle.length(), 20.0 + 200f32.sqrt()));
+    }
+
+    #[test]
+    fn arcs() {
+        let center = Point2D::new(5.0, 0.0);
+        let half_circle = ColorPath::build()
+            .begin(Point2D::new(0.0, 0.0))
+            .arc_to(
+                Vector2D::new(5.0, 5.0),
+                Angle::zero(),
+                ArcFlags::default(),
+                Point2D::new(10.0, 0.0),
+            )
+            .end(false)
+            .build();
+        assert!(approx_eq(half_circle.length(), 5.0 * PI));
+        for point in half_circle.points_at((0..10).map(|i| i as f32)) {
+            assert!(approx_eq((point.position - center).length(), 5.0));
+        }
+
+        let quarter = ColorPath::build()
+            .begin(Point2D::new(10.0, 0.0))
+            .arc(
+                center,
+                Vector2D::new(5.0, 5.0),
+                Angle::frac_pi_2(),
+                Angle::zero(),
+            )
+            .end(false)
+            .build();
+        let end = quarter.point_at(f32::INFINITY).unwrap().position;
+        assert!((end - Point2D::new(5.0, 5.0)).length() < 0.01);
+    }
+
+    #[test]
+    fn relative_commands_and_svg_agree() {
+        let relative = ColorPath::build()
+            .begin(Point2D::new(10.0, 10.0))
+            .relative_line_to(Vector2D::new(10.0, 0.0))
+            .relative_cubic_bezier_to(
+                Vector2D::new(0.0, 5.0),
+                Vector2D::new(0.0, 5.0),
+                Vector2D::new(0.0, 10.0),
+            )
+            .end(true)
+            .build();
+        let svg = ColorPath::build()
+            .svg("M10 10 h10 c0 5 0 5 0 10 z")
+            .unwrap()
+            .build();
+        assert_eq!(relative.bounding_box(), svg.bounding_box());
+        assert!(approx_eq(relative.length(), svg.length()));
+    }
+
+    #[test]
+    fn shapes_keep_colors() {
+        let mut builder = ColorPath::build();
+        builder.set_color(Color::RED);
+        let path = builder
+            .add_rect(Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 10.0)))
+            .svg("M20 0 a5 5 0 0 0 10 0")
+            .unwrap()
+            .build();
+        for point in path.points_at([0.0, 15.0, 45.0]) {
+            assert_eq!(point.color, LinearRgb::from(Color::RED));
+        }
+    }
+
+    #[test]
+    fn hdr_colors_are_not_clamped() {
+        let mut builder = ColorPath::build();
+        builder.set_color(LinearRgb::new(2.0, 1.0, 0.5, 1.0));
+        let path = builder
+            .add_rect(Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 10.0)))
+            .build();
+        for event in path.0.iter_with_attributes() {
+            if let lyon::path::Event::Line { to: (_, color), .. } = event {
+                assert_eq!(color, &[2.0, 1.0, 0.5, 1.0]);
+            }
+        }
+    }
+
+    #[test]
+    fn shapes_map_texture_coordinates() {
+        let rect = Box2D::new(Point2D::new(10.0, 10.0), Point2D::new(30.0, 20.0));
+        let uv = Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(0.5, 1.0));
+        let path = TexturePath::build(Image::new(1, 1))
+            .add_rect(rect, uv)
+            .build();
+        for event in path.0.iter_with_attributes() {
+            if let lyon::path::Event::Line { to: (at, uv), .. } = event {
+                assert_eq!(uv, &[(at.x - 10.0) / 40.0, (at.y - 10.0) / 10.0]);
+            }
+        }
+
+        // Arcs interpolate from the current texture coordinates.
+        let path = TexturePath::build(Image::new(1, 1))
+            .begin(Point2D::new(0.0, 0.0), Point2D::new(0.0, 0.0))
+            .arc_to(
+                Vector2D::new(5.0, 5.0),
+                Angle::zero(),
+                ArcFlags::default(),
+                Point2D::new(10.0, 0.0),
+                Point2D::new(1.0, 0.0),
+            )
+            .end(false)
+            .build();
+        let uvs = path
+            .0
+            .iter_with_attributes()
+            .filter_map(|event| match event {
+                lyon::path::Event::Quadratic { to: (_, uv), .. } => Some(uv[0]),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(uvs.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(uvs.last(), Some(&1.0));
     }
 }
@@ -0,0 +1,525 @@
+use std::collections::{HashMap, HashSet};
+
+use lyon::{math::point, tessellation::FillOptions};
+
+use super::{
+    segments::{Core, Segment},
+    ColorPath, FillRule,
+};
+use crate::colors::LinearRgb;
+
+type Point = euclid::default::Point2D<f64>;
+type Vector = euclid::default::Vector2D<f64>;
+
+/// The grid that vertices are snapped to, in logical pixels. Snapping lets nearly coincident
+/// points from the two paths meet exactly.
+const GRID: f64 = 1.0 / 4096.0;
+/// How far to either side of an edge its surroundings are sampled.
+const OFFSET: f64 = GRID / 8.0;
+
+/// A way of combining the filled areas of two paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BooleanOp {
+    /// Areas inside either path.
+    Union,
+    /// Areas inside both paths.
+    Intersection,
+    /// Areas inside the first path but not the second.
+    Difference,
+    /// Areas inside exactly one of the paths.
+    Xor,
+}
+impl BooleanOp {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::Xor => a != b,
+        }
+    }
+}
+
+/// How the result of a boolean operation is colored.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BooleanColors {
+    /// Each edge keeps the colors of the path it came from, interpolated where it was cut.
+    #[default]
+    Inherit,
+    /// The whole result is one color.
+    Solid(LinearRgb),
+}
+
+/// Options for boolean operations on paths.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BooleanOptions {
+    /// How far flattened curves may stray from the real ones, in logical pixels.
+    pub tolerance: f32,
+    /// How the first path is filled.
+    pub fill_rule: FillRule,
+    /// How the second path is filled.
+    pub other_fill_rule: FillRule,
+    pub colors: BooleanColors,
+}
+impl Default for BooleanOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: FillOptions::DEFAULT_TOLERANCE,
+            fill_rule: FillOptions::DEFAULT_FILL_RULE,
+            other_fill_rule: FillOptions::DEFAULT_FILL_RULE,
+            colors: BooleanColors::Inherit,
+        }
+    }
+}
+
+/// A straight edge of a flattened path, with the linear colors at its ends.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    from: Point,
+    to: Point,
+    from_color: [f32; 4],
+    to_color: [f32; 4],
+}
+
+type Key = (i64, i64);
+
+fn key(point: Point) -> Key {
+    (
+        (point.x / GRID).round() as i64,
+        (point.y / GRID).round() as i64,
+    )
+}
+
+fn snap(point: Point) -> Point {
+    Point::new(
+        (point.x / GRID).round() * GRID,
+        (point.y / GRID).round() * GRID,
+    )
+}
+
+fn lerp(from: [f32; 4], to: [f32; 4], t: f64) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| from[i] + (to[i] - from[i]) * t as f32)
+}
+
+/// The edges of `path` flattened to `tolerance`, with every subpath closed as it is when filled.
+fn edges(path: &ColorPath, tolerance: f32) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for polyline in path.flattened(tolerance) {
+        let points = polyline
+            .points
+            .iter()
+            .map(|point| {
+                let position = Point::new(point.position.x as f64, point.position.y as f64);
+                (snap(position), point.color.to_array())
+            })
+            .collect::<Vec<_>>();
+        for (i, &(from, from_color)) in points.iter().enumerate() {
+            let (to, to_color) = points[(i + 1) % points.len()];
+            if key(from) != key(to) {
+                edges.push(Edge {
+                    from,
+                    to,
+                    from_color,
+                    to_color,
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// Whether `point` lies on `edge` between its ends.
+fn touches(edge: &Edge, point: Point) -> bool {
+    let direction = edge.to - edge.from;
+    let t = (point - edge.from).dot(direction) / direction.square_length();
+    let distance = (edge.from + direction * t - point).length();
+    t > 0.0 && t < 1.0 && distance < GRID / 2.0
+}
+
+/// Where `a` and `b` cross, if they do away from their ends.
+fn crossing(a: &Edge, b: &Edge) -> Option<Point> {
+    let (r, s) = (a.to - a.from, b.to - b.from);
+    let denominator = r.cross(s);
+    if denominator.abs() <= 1e-12 * r.length() * s.length() {
+        return None;
+    }
+    let offset = b.from - a.from;
+    let t = offset.cross(s) / denominator;
+    let u = offset.cross(r) / denominator;
+    (t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then(|| a.from + r * t)
+}
+
+/// Splits `edges` wherever they cross or touch each other, so no two pieces cross.
+fn split(edges: &[Edge]) -> Vec<Vec<Edge>> {
+    let mut cuts = vec![Vec::new(); edges.len()];
+    let mut order = (0..edges.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        let min_x = |edge: &Edge| edge.from.x.min(edge.to.x);
+        min_x(&edges[a]).total_cmp(&min_x(&edges[b]))
+    });
+    for (n, &i) in order.iter().enumerate() {
+        let a = &edges[i];
+        let max_x = a.from.x.max(a.to.x) + GRID;
+        let (min_y, max_y) = (a.from.y.min(a.to.y) - GRID, a.from.y.max(a.to.y) + GRID);
+        for &j in &order[n + 1..] {
+            let b = &edges[j];
+            if b.from.x.min(b.to.x) > max_x {
+                break;
+            }
+            if b.from.y.max(b.to.y) < min_y || b.from.y.min(b.to.y) > max_y {
+                continue;
+            }
+            // Ends lying on the other edge cover T junctions and overlapping edges.
+            for point in [b.from, b.to] {
+                if touches(a, point) {
+                    cuts[i].push(point);
+                }
+            }
+            for point in [a.from, a.to] {
+                if touches(b, point) {
+                    cuts[j].push(point);
+                }
+            }
+            if let Some(point) = crossing(a, b) {
+                let point = snap(point);
+                cuts[i].push(point);
+                cuts[j].push(point);
+            }
+        }
+    }
+
+    edges
+        .iter()
+        .zip(cuts)
+        .map(|(edge, cuts)| {
+            let direction = edge.to - edge.from;
+            let mut cuts = cuts
+                .into_iter()
+                .map(|point| {
+                    let t = (point - edge.from).dot(direction) / direction.square_length();
+                    (t.clamp(0.0, 1.0), point)
+                })
+                .collect::<Vec<_>>();
+            cuts.sort_by(|a, b| a.0.total_cmp(&b.0));
+            cuts.push((1.0, edge.to));
+
+            let mut pieces = Vec::new();
+            let (mut from, mut from_color) = (edge.from, edge.from_color);
+            for (t, to) in cuts {
+                if key(from) == key(to) {
+                    continue;
+                }
+                let to_color = lerp(edge.from_color, edge.to_color, t);
+                pieces.push(Edge {
+                    from,
+                    to,
+                    from_color,
+                    to_color,
+                });
+                (from, from_color) = (to, to_color);
+            }
+            pieces
+        })
+        .collect()
+}
+
+/// The winding number of `edges` around `point`.
+fn winding(edges: &[Edge], point: Point) -> i32 {
+    let mut winding = 0;
+    for edge in edges {
+        let side = (edge.to - edge.from).cross(point - edge.from);
+        if edge.from.y <= point.y {
+            if edge.to.y > point.y && side > 0.0 {
+                winding += 1;
+            }
+        } else if edge.to.y <= point.y && side < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+fn is_inside(fill_rule: FillRule, winding: i32) -> bool {
+    match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+impl ColorPath {
+    /// Combines the filled areas of this path and `other` with `op`.
+    ///
+    /// Curves are flattened to `options.tolerance` first, and open subpaths are closed as they are
+    /// when filled. Either path may intersect itself. The result's outlines never cross and wind
+    /// the same way, so it fills the same with either fill rule.
+    pub fn boolean(&self, other: &ColorPath, op: BooleanOp, options: &BooleanOptions) -> ColorPath {
+        let own = edges(self, options.tolerance);
+        let own_count = own.len();
+        let pieces = split(&[own, edges(other, options.tolerance)].concat());
+        let (own, others) = pieces.split_at(own_count);
+        let (own, others) = (own.concat(), others.concat());
+
+        let inside = |point: Point| {
+            op.apply(
+                is_inside(options.fill_rule, winding(&own, point)),
+                is_inside(options.other_fill_rule, winding(&others, point)),
+            )
+        };
+        // Keep each piece that separates the result from the outside, turned so the result is on
+        // its left. Pieces the paths share are only kept once.
+        let mut seen = HashSet::new();
+        let mut kept = Vec::new();
+        for edge in own.iter().chain(&others) {
+            let (from, to) = (key(edge.from), key(edge.to));
+            if !seen.insert((from.min(to), from.max(to))) {
+                continue;
+            }
+            let direction = edge.to - edge.from;
+            let normal = Vector::new(-direction.y, direction.x).normalize() * OFFSET;
+            let middle = edge.from.lerp(edge.to, 0.5);
+            match (inside(middle + normal), inside(middle - normal)) {
+                (true, false) => kept.push(*edge),
+                (false, true) => kept.push(Edge {
+                    from: edge.to,
+                    to: edge.from,
+                    from_color: edge.to_color,
+                    to_color: edge.from_color,
+                }),
+                _ => {}
+            }
+        }
+        if let BooleanColors::Solid(color) = &options.colors {
+            let color = color.to_array();
+            for edge in &mut kept {
+                edge.from_color = color;
+                edge.to_color = color;
+            }
+        }
+
+        // Chain the kept pieces into closed outlines.
+        let mut outgoing = HashMap::<Key, Vec<usize>>::new();
+        for (i, edge) in kept.iter().enumerate() {
+            outgoing.entry(key(edge.from)).or_default().push(i);
+        }
+        let mut used = vec![false; kept.len()];
+        let mut core = Core::new(4);
+        let position = |at: Point| point(at.x as f32, at.y as f32);
+        for start in 0..kept.len() {
+            if used[start] {
+                continue;
+            }
+            used[start] = true;
+            let first = &kept[start];
+            core.push(Segment::Begin(position(first.from)), &first.from_color);
+            let mut current = start;
+            loop {
+                let edge = &kept[current];
+                if key(edge.to) == key(first.from) {
+                    break;
+                }
+                core.push(
+                    Segment::Line {
+                        to: position(edge.to),
+                        t: 1.0,
+                    },
+                    &edge.to_color,
+                );
+                let next = outgoing
+                    .get(&key(edge.to))
+                    .and_then(|edges| edges.iter().copied().find(|&next| !used[next]));
+                let Some(next) = next else {
+                    break;
+                };
+                used[next] = true;
+                current = next;
+            }
+            core.push(Segment::End { close: true }, &[]);
+        }
+        ColorPath(core.build())
+    }
+    /// The areas inside either this path or `other`, with default options.
+    pub fn union(&self, other: &ColorPath) -> ColorPath {
+        self.boolean(other, BooleanOp::Union, &BooleanOptions::default())
+    }
+    /// The areas inside both this path and `other`, with default options.
+    pub fn intersection(&self, other: &ColorPath) -> ColorPath {
+        self.boolean(other, BooleanOp::Intersection, &BooleanOptions::default())
+    }
+    /// The areas inside this path but not `other`, with default options.
+    pub fn difference(&self, other: &ColorPath) -> ColorPath {
+        self.boolean(other, BooleanOp::Difference, &BooleanOptions::default())
+    }
+    /// The areas inside exactly one of this path and `other`, with default options.
+    pub fn xor(&self, other: &ColorPath) -> ColorPath {
+        self.boolean(other, BooleanOp::Xor, &BooleanOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon::path::Event;
+
+    use crate::{
+        colors::Color,
+        math::{Point2D, Vector2D},
+        path::{ColorPathBuilder, Polyline},
+    };
+
+    fn square(min: (f32, f32), size: f32, color: impl Into<LinearRgb>) -> ColorPath {
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(color);
+        builder
+            .add_rect(crate::math::Box2D::new(
+                Point2D::new(min.0, min.1),
+                Point2D::new(min.0 + size, min.1 + size),
+            ))
+            .build()
+    }
+
+    /// The signed area of a path's outlines, positive when they wind like the results do.
+    fn area(path: &ColorPath) -> f32 {
+        path.flattened(0.01)
+            .iter()
+            .map(|Polyline { points, .. }| {
+                let mut area = 0.0;
+                for (i, from) in points.iter().enumerate() {
+                    let to = &points[(i + 1) % points.len()].position;
+                    area += from.position.to_vector().cross(to.to_vector());
+                }
+                area / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn combines_overlapping_squares() {
+        let a = square((0.0, 0.0), 10.0, Color::RED);
+        let b = square((5.0, 5.0), 10.0, Color::BLUE);
+        for (result, expected) in [
+            (a.union(&b), 175.0),
+            (a.intersection(&b), 25.0),
+            (a.difference(&b), 75.0),
+            (a.xor(&b), 150.0),
+        ] {
+            assert!((area(&result) - expected).abs() < 1e-2, "{}", area(&result));
+        }
+
+        let xor = a.xor(&b);
+        let inside = |x, y| xor.contains_point(Point2D::new(x, y), FillRule::NonZero);
+        assert!(inside(2.0, 2.0) && inside(12.0, 12.0));
+        assert!(!inside(7.0, 7.0) && !inside(20.0, 20.0));
+        // The result fills the same with either rule.
+        assert!(!xor.contains_point(Point2D::new(7.0, 7.0), FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn merges_shared_edges() {
+        let a = square((0.0, 0.0), 10.0, Color::RED);
+        let b = square((10.0, 0.0), 10.0, Color::RED);
+        let union = a.union(&b);
+        assert!((area(&union) - 200.0).abs() < 1e-2);
+        assert_eq!(union.flattened(0.1).len(), 1);
+        assert!(area(&a.intersection(&b)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn cuts_holes() {
+        let outer = square((0.0, 0.0), 30.0, Color::RED);
+        let inner = square((10.0, 10.0), 10.0, Color::RED);
+        let ring = outer.difference(&inner);
+        assert!((area(&ring) - 800.0).abs() < 1e-2);
+        assert!(!ring.contains_point(Point2D::new(15.0, 15.0), FillRule::NonZero));
+        assert!(ring.contains_point(Point2D::new(5.0, 15.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn flattens_curves() {
+        let circle = ColorPathBuilder::new()
+            .add_circle(Point2D::new(0.0, 0.0), 10.0)
+            .build();
+        let quadrant = square((0.0, 0.0), 20.0, Color::BLACK);
+        let options = BooleanOptions {
+            tolerance: 0.001,
+            ..Default::default()
+        };
+        let corner = circle.boolean(&quadrant, BooleanOp::Intersection, &options);
+        assert!((area(&corner) - area(&circle) / 4.0).abs() < 0.1);
+        assert_eq!(corner.bounding_box().min.round(), Point2D::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn self_intersecting_paths_follow_the_fill_rule() {
+        // A pentagram: its middle has a winding number of 2.
+        let points = (0..5)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * (i * 2) as f32 / 5.0;
+                Point2D::new(0.0, 0.0) + Vector2D::new(angle.sin(), -angle.cos()) * 10.0
+            })
+            .collect::<Vec<_>>();
+        let star = ColorPathBuilder::new().add_polygon(&points, true).build();
+        let empty = ColorPathBuilder::new().build();
+        let center = Point2D::new(0.0, 0.0);
+        for (fill_rule, filled) in [(FillRule::NonZero, true), (FillRule::EvenOdd, false)] {
+            let options = BooleanOptions {
+                fill_rule,
+                ..Default::default()
+            };
+            let simplified = star.boolean(&empty, BooleanOp::Union, &options);
+            assert_eq!(simplified.contains_point(center, FillRule::NonZero), filled);
+            assert!(simplified.contains_point(Point2D::new(0.0, -8.0), FillRule::NonZero));
+        }
+    }
+
+    #[test]
+    fn colors_results() {
+        let a = square((0.0, 0.0), 10.0, Color::RED);
+        let b = square((5.0, 5.0), 10.0, Color::BLUE);
+        let colors = |path: &ColorPath| {
+            path.flattened(0.1)
+                .into_iter()
+                .flat_map(|polyline| polyline.points)
+                .map(|point| Color::from(point.color))
+                .collect::<HashSet<_>>()
+        };
+        assert_eq!(
+            colors(&a.union(&b)),
+            HashSet::from([Color::RED, Color::BLUE])
+        );
+        let options = BooleanOptions {
+            colors: BooleanColors::Solid(Color::GREEN.into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            colors(&a.boolean(&b, BooleanOp::Union, &options)),
+            HashSet::from([Color::GREEN])
+        );
+    }
+
+    #[test]
+    fn keeps_hdr_colors() {
+        let bright = LinearRgb::new(4.0, 2.0, 0.5, 1.0);
+        let a = square((0.0, 0.0), 10.0, bright);
+        let b = square((5.0, 5.0), 10.0, bright);
+        for path in [&a, &a.union(&b)] {
+            for point in path.flattened(0.1).into_iter().flat_map(|line| line.points) {
+                assert_eq!(point.color, bright);
+            }
+        }
+        let solid = LinearRgb::new(0.0, 8.0, 0.0, 1.0);
+        let options = BooleanOptions {
+            colors: BooleanColors::Solid(solid),
+            ..Default::default()
+        };
+        let union = a.boolean(&b, BooleanOp::Union, &options);
+        assert!(union.events().all(|event| match event {
+            Event::Begin { at } => at.color == solid,
+            Event::Line { to, .. } => to.color == solid,
+            _ => true,
+        }));
+    }
+}
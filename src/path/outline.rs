@@ -0,0 +1,183 @@
+use lyon::{
+    lyon_tessellation::{
+        BuffersBuilder, StrokeTessellator, StrokeVertex, TessellationError, VertexBuffers,
+    },
+    math::Point,
+    path::{path::BuilderWithAttributes, Event},
+};
+
+use super::{BooleanOp, BooleanOptions, ColorPath, FillRule, LineJoin, StrokeOptions};
+
+impl ColorPath {
+    /// The area covered by stroking the path with `options`, as a path that can be filled.
+    ///
+    /// Colors are interpolated along the stroke as they are when it is drawn.
+    pub fn stroke_to_path(&self, options: StrokeOptions) -> Result<ColorPath, TessellationError> {
+        let mut geometry: VertexBuffers<(Point, [f32; 4]), u32> = VertexBuffers::new();
+        StrokeTessellator::new().tessellate_path(
+            &self.0,
+            &options,
+            &mut BuffersBuilder::new(&mut geometry, |mut vertex: StrokeVertex| {
+                let position = vertex.position();
+                let attributes = vertex.interpolated_attributes();
+                (
+                    position,
+                    [attributes[0], attributes[1], attributes[2], attributes[3]],
+                )
+            }),
+        )?;
+
+        // Wind every triangle the same way, so the nonzero rule fills their overlaps once.
+        let mut triangles = BuilderWithAttributes::new(4);
+        for triangle in geometry.indices.chunks_exact(3) {
+            let [a, mut b, mut c] = [0, 1, 2].map(|i| geometry.vertices[triangle[i] as usize]);
+            let winding = (b.0 - a.0).cross(c.0 - a.0);
+            if winding.abs() <= f32::EPSILON {
+                continue;
+            }
+            if winding < 0.0 {
+                (b, c) = (c, b);
+            }
+            triangles.begin(a.0, &a.1);
+            triangles.line_to(b.0, &b.1);
+            triangles.line_to(c.0, &c.1);
+            triangles.end(true);
+        }
+        let options = BooleanOptions {
+            fill_rule: FillRule::NonZero,
+            ..Default::default()
+        };
+        Ok(ColorPath(triangles.build()).boolean(&ColorPath::empty(), BooleanOp::Union, &options))
+    }
+    /// The path's filled area grown by `distance`, or shrunk if `distance` is negative, with
+    /// corners shaped by `join`. The path is filled with the default fill rule.
+    pub fn offset(&self, distance: f32, join: LineJoin) -> Result<ColorPath, TessellationError> {
+        if distance == 0.0 {
+            return Ok(self.boolean(&ColorPath::empty(), BooleanOp::Union, &Default::default()));
+        }
+        let border = self.closed().stroke_to_path(
+            StrokeOptions::default()
+                .with_line_width(distance.abs() * 2.0)
+                .with_line_join(join),
+        )?;
+        let op = if distance > 0.0 {
+            BooleanOp::Union
+        } else {
+            BooleanOp::Difference
+        };
+        let options = BooleanOptions {
+            other_fill_rule: FillRule::NonZero,
+            ..Default::default()
+        };
+        Ok(self.boolean(&border, op, &options))
+    }
+    fn empty() -> ColorPath {
+        ColorPath(lyon::path::Path::new())
+    }
+    /// A copy of the path with every subpath closed, as they are when filled.
+    fn closed(&self) -> ColorPath {
+        let mut builder = BuilderWithAttributes::new(4);
+        for event in self.0.iter_with_attributes() {
+            match event {
+                Event::Begin { at } => {
+                    builder.begin(at.0, at.1);
+                }
+                Event::Line { to, .. } => {
+                    builder.line_to(to.0, to.1);
+                }
+                Event::Quadratic { ctrl, to, .. } => {
+                    builder.quadratic_bezier_to(ctrl, to.0, to.1);
+                }
+                Event::Cubic {
+                    ctrl1, ctrl2, to, ..
+                } => {
+                    builder.cubic_bezier_to(ctrl1, ctrl2, to.0, to.1);
+                }
+                Event::End { .. } => builder.end(true),
+            }
+        }
+        ColorPath(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        colors::{Color, LinearRgb},
+        math::{Box2D, Point2D},
+        path::{ColorPathBuilder, LineCap},
+    };
+
+    fn area(path: &ColorPath) -> f32 {
+        path.flattened(0.01)
+            .iter()
+            .map(|polyline| {
+                let points = &polyline.points;
+                let mut area = 0.0;
+                for (i, from) in points.iter().enumerate() {
+                    let to = &points[(i + 1) % points.len()].position;
+                    area += from.position.to_vector().cross(to.to_vector());
+                }
+                area / 2.0
+            })
+            .sum()
+    }
+
+    fn square() -> ColorPath {
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(Color::RED);
+        builder
+            .add_rect(Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 10.0)))
+            .build()
+    }
+
+    #[test]
+    fn outlines_strokes() {
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(Color::BLUE);
+        let line = builder
+            .begin(Point2D::new(0.0, 0.0))
+            .line_to(Point2D::new(10.0, 0.0))
+            .line_to(Point2D::new(10.0, 10.0))
+            .build();
+        let outline = line
+            .stroke_to_path(
+                StrokeOptions::default()
+                    .with_line_width(2.0)
+                    .with_line_cap(LineCap::Butt)
+                    .with_line_join(LineJoin::Miter),
+            )
+            .unwrap();
+        // Two 10x2 legs overlapping at the corner, plus the mitred corner outside it.
+        assert!((area(&outline) - 40.0).abs() < 1e-2, "{}", area(&outline));
+        assert_eq!(outline.flattened(0.1).len(), 1);
+        assert!(outline
+            .flattened(0.1)
+            .iter()
+            .flat_map(|polyline| &polyline.points)
+            .all(|point| point.color == LinearRgb::from(Color::BLUE)));
+        assert!(outline.contains_point(Point2D::new(5.0, 0.5), FillRule::NonZero));
+        assert!(!outline.contains_point(Point2D::new(5.0, 5.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn offsets_shapes() {
+        let square = square();
+        let grown = square.offset(2.0, LineJoin::Miter).unwrap();
+        assert!((area(&grown) - 196.0).abs() < 1e-2, "{}", area(&grown));
+        let rounded = square.offset(2.0, LineJoin::Round).unwrap();
+        let expected = 100.0 + 4.0 * 10.0 * 2.0 + std::f32::consts::PI * 4.0;
+        assert!(
+            (area(&rounded) - expected).abs() < 0.5,
+            "{}",
+            area(&rounded)
+        );
+        let shrunk = square.offset(-2.0, LineJoin::Miter).unwrap();
+        assert!((area(&shrunk) - 36.0).abs() < 1e-2, "{}", area(&shrunk));
+        assert_eq!(
+            shrunk.bounding_box(),
+            Box2D::new(Point2D::new(2.0, 2.0), Point2D::new(8.0, 8.0))
+        );
+    }
+}
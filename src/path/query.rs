@@ -0,0 +1,205 @@
+use lyon::{
+    algorithms::{
+        aabb::bounding_box,
+        hit_test::hit_test_path,
+        measure::{PathMeasurements, SampleType},
+    },
+    geom::LineSegment,
+    path::{iterator::PathIterator, Event},
+};
+
+use super::{ColorPath, FillRule, StrokeOptions};
+use crate::{
+    colors::LinearRgb,
+    math::{Box2D, Point2D, Vector2D},
+};
+
+/// How far flattened curves may stray from the real ones in queries, in logical pixels.
+const TOLERANCE: f32 = 0.01;
+
+/// A point on the outline of a `ColorPath`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathPoint {
+    pub position: Point2D,
+    /// The unit direction the path is heading in at `position`.
+    pub tangent: Vector2D,
+    /// How far along the path `position` is, from the start of its first subpath.
+    pub distance: f32,
+    /// The path's interpolated color at `position`.
+    pub color: LinearRgb,
+}
+
+impl ColorPath {
+    /// Whether `point` is inside the path when filled with `fill_rule`.
+    pub fn contains_point(&self, point: Point2D, fill_rule: FillRule) -> bool {
+        hit_test_path(&point.to_untyped(), self.0.iter(), fill_rule, TOLERANCE)
+    }
+    /// Whether `point` is on the path when stroked with `stroke`, or within `tolerance` of it.
+    ///
+    /// Caps and joins are treated as round.
+    pub fn stroke_contains_point(
+        &self,
+        point: Point2D,
+        stroke: &StrokeOptions,
+        tolerance: f32,
+    ) -> bool {
+        self.nearest_point(point).is_some_and(|nearest| {
+            (nearest.position - point).length() <= stroke.line_width / 2.0 + tolerance
+        })
+    }
+    /// The smallest box containing the path, including its curves.
+    pub fn bounding_box(&self) -> Box2D {
+        Box2D::from_untyped(&bounding_box(self.0.iter()))
+    }
+    /// The length of the path's outline, including the segments that close subpaths.
+    pub fn length(&self) -> f32 {
+        PathMeasurements::from_path(&self.0, TOLERANCE).length()
+    }
+    /// The point `distance` along the path, clamped to its ends. Returns `None` for empty paths.
+    pub fn point_at(&self, distance: f32) -> Option<PathPoint> {
+        self.points_at([distance]).pop()
+    }
+    /// The points at each of `distances` along the path, measuring the path only once.
+    pub fn points_at(&self, distances: impl IntoIterator<Item = f32>) -> Vec<PathPoint> {
+        if self.0.iter().next().is_none() {
+            return Vec::new();
+        }
+        let measurements = PathMeasurements::from_path(&self.0, TOLERANCE);
+        let length = measurements.length();
+        let mut sampler =
+            measurements.create_sampler_with_attributes(&self.0, &self.0, SampleType::Distance);
+        distances
+            .into_iter()
+            .map(|distance| {
+                let distance = distance.clamp(0.0, length);
+                let mut sample = sampler.sample(distance);
+                let attributes = sample.attributes();
+                PathPoint {
+                    position: Point2D::from_untyped(sample.position()),
+                    tangent: Vector2D::from_untyped(sample.tangent().normalize()),
+                    distance,
+                    color: LinearRgb::new(
+                        attributes[0],
+                        attributes[1],
+                        attributes[2],
+                        attributes[3],
+                    ),
+                }
+            })
+            .collect()
+    }
+    /// The point on the path's outline closest to `point`. Returns `None` for empty paths.
+    pub fn nearest_point(&self, point: Point2D) -> Option<PathPoint> {
+        let target = point.to_untyped();
+        let mut nearest = None;
+        let mut nearest_distance = f32::INFINITY;
+        let mut along = 0.0;
+        for event in self.0.iter().flattened(TOLERANCE) {
+            let segment = match event {
+                Event::Begin { at } => LineSegment { from: at, to: at },
+                Event::Line { from, to, .. } => LineSegment { from, to },
+                Event::End {
+                    last,
+                    first,
+                    close: true,
+                } => LineSegment {
+                    from: last,
+                    to: first,
+                },
+                _ => continue,
+            };
+            let closest = segment.closest_point(target);
+            let distance = (closest - target).square_length();
+            if distance < nearest_distance {
+                nearest_distance = distance;
+                nearest = Some((closest, along + (closest - segment.from).length()));
+            }
+            along += segment.length();
+        }
+        let (position, distance) = nearest?;
+        self.point_at(distance).map(|sample| PathPoint {
+            position: Point2D::from_untyped(position),
+            ..sample
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colors::Color, path::ColorPathBuilder};
+
+    fn square() -> ColorPath {
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(Color::RED);
+        let builder = builder
+            .begin(Point2D::new(0.0, 0.0))
+            .line_to(Point2D::new(10.0, 0.0))
+            .line_to(Point2D::new(10.0, 10.0))
+            .line_to(Point2D::new(0.0, 10.0))
+            .end(true);
+        builder.build()
+    }
+
+    #[test]
+    fn fill_rules() {
+        // Two overlapping squares wound the same way: the overlap has a winding number of 2.
+        let path = ColorPathBuilder::new()
+            .begin(Point2D::new(0.0, 0.0))
+            .line_to(Point2D::new(10.0, 0.0))
+            .line_to(Point2D::new(10.0, 10.0))
+            .line_to(Point2D::new(0.0, 10.0))
+            .end(true)
+            .begin(Point2D::new(5.0, 5.0))
+            .line_to(Point2D::new(15.0, 5.0))
+            .line_to(Point2D::new(15.0, 15.0))
+            .line_to(Point2D::new(5.0, 15.0))
+            .end(true)
+            .build();
+        let overlap = Point2D::new(7.0, 7.0);
+        assert!(path.contains_point(overlap, FillRule::NonZero));
+        assert!(!path.contains_point(overlap, FillRule::EvenOdd));
+        assert!(path.contains_point(Point2D::new(2.0, 2.0), FillRule::EvenOdd));
+        assert!(!path.contains_point(Point2D::new(20.0, 2.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn measures_closed_paths() {
+        let path = square();
+        assert!((path.length() - 40.0).abs() < 1e-4);
+        assert_eq!(
+            path.bounding_box(),
+            Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 10.0))
+        );
+        let point = path.point_at(15.0).unwrap();
+        assert!((point.position - Point2D::new(10.0, 5.0)).length() < 1e-4);
+        assert!((point.tangent - Vector2D::new(0.0, 1.0)).length() < 1e-4);
+        assert_eq!(point.color, LinearRgb::from(Color::RED));
+        // The closing segment counts towards the length.
+        let point = path.point_at(35.0).unwrap();
+        assert!((point.position - Point2D::new(0.0, 5.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn nearest_point_and_stroke_hits() {
+        let path = square();
+        let nearest = path.nearest_point(Point2D::new(13.0, 4.0)).unwrap();
+        assert!((nearest.position - Point2D::new(10.0, 4.0)).length() < 1e-4);
+        assert!((nearest.distance - 14.0).abs() < 1e-3);
+
+        let stroke = StrokeOptions::default().with_line_width(2.0);
+        assert!(path.stroke_contains_point(Point2D::new(10.5, 4.0), &stroke, 0.0));
+        assert!(!path.stroke_contains_point(Point2D::new(12.0, 4.0), &stroke, 0.0));
+        assert!(path.stroke_contains_point(Point2D::new(12.0, 4.0), &stroke, 1.0));
+        assert!(!path.stroke_contains_point(Point2D::new(5.0, 5.0), &stroke, 1.0));
+    }
+
+    #[test]
+    fn empty_paths_have_no_points() {
+        let path = ColorPathBuilder::new().build();
+        assert_eq!(path.length(), 0.0);
+        assert!(path.point_at(1.0).is_none());
+        assert!(path.nearest_point(Point2D::new(0.0, 0.0)).is_none());
+    }
+}
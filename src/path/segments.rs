@@ -0,0 +1,309 @@
+use lyon::{
+    geom::{Arc, ArcFlags, CubicBezierSegment, LineSegment, QuadraticBezierSegment, SvgArc},
+    math::{Angle, Point, Vector},
+    path::{
+        builder::{BorderRadii, PathBuilder},
+        path::BuilderWithAttributes,
+        Attributes, EndpointId, Polygon, Winding,
+    },
+};
+
+/// A piece of a path produced by the arc and shape helpers. `t` runs from 0 to 1 over the whole
+/// arc, so builders can interpolate attributes along it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Segment {
+    Begin(Point),
+    Line {
+        to: Point,
+        t: f32,
+    },
+    Quadratic {
+        ctrl: Point,
+        to: Point,
+        t: f32,
+    },
+    Cubic {
+        ctrl1: Point,
+        ctrl2: Point,
+        to: Point,
+        t: f32,
+    },
+    End {
+        close: bool,
+    },
+}
+
+/// Where a builder is, so relative and smooth commands know what they are relative to.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Cursor {
+    pub first: Point,
+    pub current: Point,
+    /// The last control point of the previous segment, if it was a cubic curve.
+    pub cubic_ctrl: Option<Point>,
+    /// The control point of the previous segment, if it was a quadratic curve.
+    pub quadratic_ctrl: Option<Point>,
+    /// Whether a subpath is in progress.
+    pub open: bool,
+}
+impl Cursor {
+    pub fn advance(&mut self, segment: &Segment) {
+        self.cubic_ctrl = None;
+        self.quadratic_ctrl = None;
+        match *segment {
+            Segment::Begin(at) => {
+                self.first = at;
+                self.current = at;
+                self.open = true;
+            }
+            Segment::Line { to, .. } => self.current = to,
+            Segment::Quadratic { ctrl, to, .. } => {
+                self.quadratic_ctrl = Some(ctrl);
+                self.current = to;
+            }
+            Segment::Cubic { ctrl2, to, .. } => {
+                self.cubic_ctrl = Some(ctrl2);
+                self.current = to;
+            }
+            Segment::End { close } => {
+                if close {
+                    self.current = self.first;
+                }
+                self.open = false;
+            }
+        }
+    }
+    /// The first control point of a smooth cubic curve: the reflection of the previous one.
+    pub fn smooth_cubic_ctrl(&self) -> Point {
+        self.cubic_ctrl
+            .map_or(self.current, |ctrl| self.current + (self.current - ctrl))
+    }
+    /// The control point of a smooth quadratic curve: the reflection of the previous one.
+    pub fn smooth_quadratic_ctrl(&self) -> Point {
+        self.quadratic_ctrl
+            .map_or(self.current, |ctrl| self.current + (self.current - ctrl))
+    }
+}
+
+/// A path builder that remembers the segments of the shape lyon built into it.
+struct Recorder {
+    segments: Vec<Segment>,
+}
+impl PathBuilder for Recorder {
+    fn num_attributes(&self) -> usize {
+        0
+    }
+    fn begin(&mut self, at: Point, _attributes: Attributes) -> EndpointId {
+        self.segments.push(Segment::Begin(at));
+        EndpointId(self.segments.len() as u32)
+    }
+    fn end(&mut self, close: bool) {
+        self.segments.push(Segment::End { close });
+    }
+    fn line_to(&mut self, to: Point, _attributes: Attributes) -> EndpointId {
+        self.segments.push(Segment::Line { to, t: 1.0 });
+        EndpointId(self.segments.len() as u32)
+    }
+    fn quadratic_bezier_to(
+        &mut self,
+        ctrl: Point,
+        to: Point,
+        _attributes: Attributes,
+    ) -> EndpointId {
+        self.segments.push(Segment::Quadratic { ctrl, to, t: 1.0 });
+        EndpointId(self.segments.len() as u32)
+    }
+    fn cubic_bezier_to(
+        &mut self,
+        ctrl1: Point,
+        ctrl2: Point,
+        to: Point,
+        _attributes: Attributes,
+    ) -> EndpointId {
+        self.segments.push(Segment::Cubic {
+            ctrl1,
+            ctrl2,
+            to,
+            t: 1.0,
+        });
+        EndpointId(self.segments.len() as u32)
+    }
+}
+
+fn record(build: impl FnOnce(&mut Recorder)) -> Vec<Segment> {
+    let mut recorder = Recorder {
+        segments: Vec::new(),
+    };
+    build(&mut recorder);
+    recorder.segments
+}
+
+pub(super) fn rect(rect: &lyon::math::Box2D) -> Vec<Segment> {
+    record(|builder| builder.add_rectangle(rect, Winding::Positive, &[]))
+}
+
+pub(super) fn rounded_rect(rect: &lyon::math::Box2D, radii: &BorderRadii) -> Vec<Segment> {
+    record(|builder| builder.add_rounded_rectangle(rect, radii, Winding::Positive, &[]))
+}
+
+pub(super) fn ellipse(center: Point, radii: Vector, x_rotation: Angle) -> Vec<Segment> {
+    record(|builder| builder.add_ellipse(center, radii, x_rotation, Winding::Positive, &[]))
+}
+
+pub(super) fn polygon(points: &[Point], closed: bool) -> Vec<Segment> {
+    record(|builder| builder.add_polygon(Polygon { points, closed }, &[]))
+}
+
+/// An arc around `center` starting at the cursor, joined to the current subpath with a line if
+/// the cursor isn't on it.
+pub(super) fn arc(
+    cursor: &Cursor,
+    center: Point,
+    radii: Vector,
+    sweep_angle: Angle,
+    x_rotation: Angle,
+) -> Vec<Segment> {
+    if (cursor.current - center).square_length() < f32::EPSILON {
+        return Vec::new();
+    }
+    let arc = Arc {
+        center,
+        radii,
+        start_angle: (cursor.current - center).angle_from_x_axis() - x_rotation,
+        sweep_angle,
+        x_rotation,
+    };
+    let mut segments = Vec::new();
+    if !cursor.open {
+        segments.push(Segment::Begin(arc.from()));
+    } else if (arc.from() - cursor.current).square_length() > 0.0001 {
+        segments.push(Segment::Line {
+            to: arc.from(),
+            t: 0.0,
+        });
+    }
+    arc.for_each_quadratic_bezier_with_t(&mut |curve, t| {
+        segments.push(Segment::Quadratic {
+            ctrl: curve.ctrl,
+            to: curve.to,
+            t: t.end,
+        });
+    });
+    segments
+}
+
+/// An SVG elliptical arc from the cursor to `to`.
+pub(super) fn svg_arc(
+    cursor: &Cursor,
+    radii: Vector,
+    x_rotation: Angle,
+    flags: ArcFlags,
+    to: Point,
+) -> Vec<Segment> {
+    let arc = SvgArc {
+        from: cursor.current,
+        to,
+        radii,
+        x_rotation,
+        flags,
+    };
+    if arc.is_straight_line() {
+        return vec![Segment::Line { to, t: 1.0 }];
+    }
+    let mut segments = Vec::new();
+    arc.to_arc()
+        .for_each_quadratic_bezier_with_t(&mut |curve, t| {
+            segments.push(Segment::Quadratic {
+                ctrl: curve.ctrl,
+                to: curve.to,
+                t: t.end,
+            });
+        });
+    // Land exactly on `to`, so following commands aren't thrown off by rounding.
+    if let Some(Segment::Quadratic { to: last, .. }) = segments.last_mut() {
+        *last = to;
+    }
+    segments
+}
+
+/// The smallest box containing `segments`, which start from `from`.
+pub(super) fn bounds(mut from: Point, segments: &[Segment]) -> lyon::math::Box2D {
+    // Boxes with no area count as empty when unioned, so collect corners instead.
+    let mut corners = Vec::new();
+    let mut include = |bounds: lyon::math::Box2D| corners.extend([bounds.min, bounds.max]);
+    for segment in segments {
+        match *segment {
+            Segment::Begin(at) => {
+                include(lyon::math::Box2D::new(at, at));
+                from = at;
+            }
+            Segment::Line { to, .. } => {
+                include(LineSegment { from, to }.bounding_box());
+                from = to;
+            }
+            Segment::Quadratic { ctrl, to, .. } => {
+                include(QuadraticBezierSegment { from, ctrl, to }.bounding_box());
+                from = to;
+            }
+            Segment::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => {
+                include(
+                    CubicBezierSegment {
+                        from,
+                        ctrl1,
+                        ctrl2,
+                        to,
+                    }
+                    .bounding_box(),
+                );
+                from = to;
+            }
+            Segment::End { .. } => {}
+        }
+    }
+    lyon::math::Box2D::from_points(corners)
+}
+
+/// A lyon builder along with where it is.
+pub(super) struct Core {
+    pub builder: BuilderWithAttributes,
+    pub cursor: Cursor,
+}
+impl Core {
+    pub fn new(num_attributes: usize) -> Self {
+        Self {
+            builder: BuilderWithAttributes::new(num_attributes),
+            cursor: Cursor::default(),
+        }
+    }
+    pub fn push(&mut self, segment: Segment, attributes: Attributes) {
+        match segment {
+            Segment::Begin(at) => {
+                if self.cursor.open {
+                    self.builder.end(false);
+                }
+                self.builder.begin(at, attributes);
+            }
+            Segment::Line { to, .. } => {
+                self.builder.line_to(to, attributes);
+            }
+            Segment::Quadratic { ctrl, to, .. } => {
+                self.builder.quadratic_bezier_to(ctrl, to, attributes);
+            }
+            Segment::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => {
+                self.builder.cubic_bezier_to(ctrl1, ctrl2, to, attributes);
+            }
+            Segment::End { close } => self.builder.end(close),
+        }
+        self.cursor.advance(&segment);
+    }
+    /// Ends the subpath in progress, if any.
+    pub fn build(mut self) -> lyon::path::Path {
+        if self.cursor.open {
+            self.builder.end(false);
+        }
+        self.builder.build()
+    }
+}
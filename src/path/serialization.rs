@@ -0,0 +1,255 @@
+use lyon::path::{path::BuilderWithAttributes, Event};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{ColorPath, ColorPoint, TexturePath};
+use crate::{colors::LinearRgb, math::Point2D, rendering::Image};
+
+/// A path command, as paths are serialized.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Command<P> {
+    Begin {
+        at: P,
+    },
+    Line {
+        to: P,
+    },
+    Quadratic {
+        ctrl: Point2D,
+        to: P,
+    },
+    Cubic {
+        ctrl1: Point2D,
+        ctrl2: Point2D,
+        to: P,
+    },
+    End {
+        close: bool,
+    },
+}
+
+/// An endpoint of a `TexturePath`, with its texture coordinates.
+#[derive(Serialize, Deserialize)]
+struct TexturePoint {
+    position: Point2D,
+    uv: Point2D,
+}
+
+/// The commands of `path`, with each endpoint's attributes converted by `point`.
+fn commands<'a, P>(
+    path: &'a lyon::path::Path,
+    point: impl Fn(lyon::math::Point, &[f32]) -> P + 'a,
+) -> impl Iterator<Item = Command<P>> + 'a {
+    path.iter_with_attributes().map(move |event| match event {
+        Event::Begin { at } => Command::Begin {
+            at: point(at.0, at.1),
+        },
+        Event::Line { to, .. } => Command::Line {
+            to: point(to.0, to.1),
+        },
+        Event::Quadratic { ctrl, to, .. } => Command::Quadratic {
+            ctrl: Point2D::from_untyped(ctrl),
+            to: point(to.0, to.1),
+        },
+        Event::Cubic {
+            ctrl1, ctrl2, to, ..
+        } => Command::Cubic {
+            ctrl1: Point2D::from_untyped(ctrl1),
+            ctrl2: Point2D::from_untyped(ctrl2),
+            to: point(to.0, to.1),
+        },
+        Event::End { close, .. } => Command::End { close },
+    })
+}
+
+/// Builds a path from `commands`, rejecting ones that don't form whole subpaths.
+fn build<P, E: Error, const N: usize>(
+    commands: Vec<Command<P>>,
+    point: impl Fn(P) -> (Point2D, [f32; N]),
+) -> Result<lyon::path::Path, E> {
+    let mut builder = BuilderWithAttributes::new(N);
+    let mut open = false;
+    for command in commands {
+        let begins = matches!(command, Command::Begin { .. });
+        let ends = matches!(command, Command::End { .. });
+        if open == begins {
+            return Err(E::custom(if open {
+                "a subpath began before the previous one ended"
+            } else {
+                "a path command came before the subpath began"
+            }));
+        }
+        match command {
+            Command::Begin { at } => {
+                let (at, attributes) = point(at);
+                builder.begin(at.to_untyped(), &attributes);
+            }
+            Command::Line { to } => {
+                let (to, attributes) = point(to);
+                builder.line_to(to.to_untyped(), &attributes);
+            }
+            Command::Quadratic { ctrl, to } => {
+                let (to, attributes) = point(to);
+                builder.quadratic_bezier_to(ctrl.to_untyped(), to.to_untyped(), &attributes);
+            }
+            Command::Cubic { ctrl1, ctrl2, to } => {
+                let (to, attributes) = point(to);
+                builder.cubic_bezier_to(
+                    ctrl1.to_untyped(),
+                    ctrl2.to_untyped(),
+                    to.to_untyped(),
+                    &attributes,
+                );
+            }
+            Command::End { close } => builder.end(close),
+        }
+        open = !ends;
+    }
+    if open {
+        builder.end(false);
+    }
+    Ok(builder.build())
+}
+
+/// Color paths are written as a list of commands, with a color at each endpoint.
+impl Serialize for ColorPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(commands(&self.0, |position, attributes| ColorPoint {
+            position: Point2D::from_untyped(position),
+            color: LinearRgb::new(attributes[0], attributes[1], attributes[2], attributes[3]),
+        }))
+    }
+}
+impl<'de> Deserialize<'de> for ColorPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let commands = Vec::<Command<ColorPoint>>::deserialize(deserializer)?;
+        build(commands, |point| (point.position, point.color.to_array())).map(ColorPath)
+    }
+}
+
+/// The pixels of a texture, as `width * height` RGBA values.
+#[derive(Serialize, Deserialize)]
+struct Texture {
+    width: u32,
+    height: u32,
+    pixels: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TexturePathRepr {
+    texture: Texture,
+    commands: Vec<Command<TexturePoint>>,
+}
+
+/// Texture paths are written as their texture followed by a list of commands, with texture
+/// coordinates at each endpoint.
+impl Serialize for TexturePath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TexturePathRepr {
+            texture: Texture {
+                width: self.1.width(),
+                height: self.1.height(),
+                pixels: self.1.as_raw().clone(),
+            },
+            commands: commands(&self.0, |position, attributes| TexturePoint {
+                position: Point2D::from_untyped(position),
+                uv: Point2D::new(attributes[0], attributes[1]),
+            })
+            .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+impl<'de> Deserialize<'de> for TexturePath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let TexturePathRepr { texture, commands } = TexturePathRepr::deserialize(deserializer)?;
+        let image =
+            Image::from_raw(texture.width, texture.height, texture.pixels).ok_or_else(|| {
+                D::Error::custom("the texture's pixels don't match its width and height")
+            })?;
+        let path = build(commands, |point| (point.position, [point.uv.x, point.uv.y]))?;
+        Ok(TexturePath(path, image))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colors::Color, math::Box2D, path::ColorPathBuilder};
+
+    #[test]
+    fn color_paths_round_trip() {
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(Color::RED);
+        let mut builder = builder.begin(Point2D::new(0.0, 0.0));
+        builder.set_color(Color::BLUE);
+        let path = builder
+            .quadratic_bezier_to(Point2D::new(5.0, 10.0), Point2D::new(10.0, 0.0))
+            .end(true)
+            .build();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(
+            json,
+            r##"[{"begin":{"at":{"position":[0.0,0.0],"color":{"r":1.0,"g":0.0,"b":0.0,"alpha":1.0}}}},{"quadratic":{"ctrl":[5.0,10.0],"to":{"position":[10.0,0.0],"color":{"r":0.0,"g":0.0,"b":1.0,"alpha":1.0}}}},{"end":{"close":true}}]"##
+        );
+        let read: ColorPath = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            read.events().collect::<Vec<_>>(),
+            path.events().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn hdr_colors_round_trip() {
+        let bright = LinearRgb::new(6.0, 0.25, 1.5, 0.5);
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(bright);
+        let path = builder
+            .add_rect(Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(4.0, 2.0)))
+            .build();
+        let read: ColorPath = serde_json::from_str(&serde_json::to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            read.events().collect::<Vec<_>>(),
+            path.events().collect::<Vec<_>>()
+        );
+        assert!(read
+            .flattened(0.1)
+            .into_iter()
+            .flat_map(|polyline| polyline.points)
+            .all(|point| point.color == bright));
+    }
+
+    #[test]
+    fn texture_paths_round_trip() {
+        let image = Image::from_pixel(2, 1, image::Rgba([1.0, 0.5, 0.25, 1.0]));
+        let path = TexturePath::build(image.clone())
+            .add_rect(
+                Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(4.0, 2.0)),
+                Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0)),
+            )
+            .build();
+        let read: TexturePath =
+            serde_json::from_value(serde_json::to_value(&path).unwrap()).unwrap();
+        assert_eq!(read.1, image);
+        assert_eq!(
+            read.0.iter_with_attributes().collect::<Vec<_>>(),
+            path.0.iter_with_attributes().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        let error = serde_json::from_str::<ColorPath>(
+            r##"[{"line":{"to":{"position":[1,1],"color":{"r":0,"g":0,"b":0,"alpha":1}}}}]"##,
+        )
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("before the subpath began"));
+        let error = serde_json::from_str::<TexturePath>(
+            r#"{"texture":{"width":2,"height":2,"pixels":[]},"commands":[]}"#,
+        )
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("width and height"));
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt::Display;
+
+use lyon::{
+    geom::ArcFlags,
+    math::{point, vector, Angle, Point},
+};
+
+use super::segments::{svg_arc, Cursor, Segment};
+
+/// An error in SVG path data, with the byte offset it was found at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvgPathError {
+    /// A character that isn't a command, number or separator.
+    UnexpectedCharacter { position: usize, character: char },
+    /// A command is missing some of its numbers.
+    ExpectedNumber { position: usize },
+    /// An arc flag wasn't `0` or `1`.
+    ExpectedFlag { position: usize },
+    /// The path data doesn't start with a move command.
+    MissingMoveTo,
+}
+impl Display for SvgPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgPathError::UnexpectedCharacter {
+                position,
+                character,
+            } => write!(f, "unexpected {character:?} at {position} in path data"),
+            SvgPathError::ExpectedNumber { position } => {
+                write!(f, "expected a number at {position} in path data")
+            }
+            SvgPathError::ExpectedFlag { position } => {
+                write!(
+                    f,
+                    "expected an arc flag (0 or 1) at {position} in path data"
+                )
+            }
+            SvgPathError::MissingMoveTo => write!(f, "path data must start with a move command"),
+        }
+    }
+}
+impl std::error::Error for SvgPathError {}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+    cursor: Cursor,
+    segments: Vec<Segment>,
+}
+impl Parser<'_> {
+    fn skip_separators(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r' | b'\x0C' | b',') = self.data.get(self.position)
+        {
+            self.position += 1;
+        }
+    }
+    fn number(&mut self) -> Result<f32, SvgPathError> {
+        self.skip_separators();
+        let start = self.position;
+        let error = SvgPathError::ExpectedNumber { position: start };
+        let digits = |parser: &mut Self| {
+            let start = parser.position;
+            while let Some(b'0'..=b'9') = parser.data.get(parser.position) {
+                parser.position += 1;
+            }
+            parser.position > start
+        };
+        if let Some(b'-' | b'+') = self.data.get(self.position) {
+            self.position += 1;
+        }
+        let mut mantissa = digits(self);
+        if let Some(b'.') = self.data.get(self.position) {
+            self.position += 1;
+            mantissa |= digits(self);
+        }
+        if !mantissa {
+            self.position = start;
+            return Err(error);
+        }
+        if let Some(b'e' | b'E') = self.data.get(self.position) {
+            let before_exponent = self.position;
+            self.position += 1;
+            if let Some(b'-' | b'+') = self.data.get(self.position) {
+                self.position += 1;
+            }
+            if !digits(self) {
+                self.position = before_exponent;
+            }
+        }
+        std::str::from_utf8(&self.data[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or(error)
+    }
+    fn flag(&mut self) -> Result<bool, SvgPathError> {
+        self.skip_separators();
+        let flag = match self.data.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => {
+                return Err(SvgPathError::ExpectedFlag {
+                    position: self.position,
+                })
+            }
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+    fn point(&mut self, relative: bool) -> Result<Point, SvgPathError> {
+        let (x, y) = (self.number()?, self.number()?);
+        Ok(if relative {
+            self.cursor.current + vector(x, y)
+        } else {
+            point(x, y)
+        })
+    }
+    fn push(&mut self, segment: Segment) {
+        if !matches!(segment, Segment::Begin(_) | Segment::End { .. }) && !self.cursor.open {
+            self.push(Segment::Begin(self.cursor.current));
+        }
+        self.cursor.advance(&segment);
+        self.segments.push(segment);
+    }
+    fn command(&mut self, command: u8) -> Result<(), SvgPathError> {
+        let relative = command.is_ascii_lowercase();
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let at = self.point(relative)?;
+                if self.cursor.open {
+                    self.push(Segment::End { close: false });
+                }
+                self.push(Segment::Begin(at));
+            }
+            b'L' => {
+                let to = self.point(relative)?;
+                self.push(Segment::Line { to, t: 1.0 });
+            }
+            b'H' => {
+                let x = self.number()?;
+                let current = self.cursor.current;
+                let x = if relative { current.x + x } else { x };
+                self.push(Segment::Line {
+                    to: point(x, current.y),
+                    t: 1.0,
+                });
+            }
+            b'V' => {
+                let y = self.number()?;
+                let current = self.cursor.current;
+                let y = if relative { current.y + y } else { y };
+                self.push(Segment::Line {
+                    to: point(current.x, y),
+                    t: 1.0,
+                });
+            }
+            b'C' => {
+                let ctrl1 = self.point(relative)?;
+                let ctrl2 = self.point(relative)?;
+                let to = self.point(relative)?;
+                self.push(Segment::Cubic {
+                    ctrl1,
+                    ctrl2,
+                    to,
+                    t: 1.0,
+                });
+            }
+            b'S' => {
+                let ctrl1 = self.cursor.smooth_cubic_ctrl();
+                let ctrl2 = self.point(relative)?;
+                let to = self.point(relative)?;
+                self.push(Segment::Cubic {
+                    ctrl1,
+                    ctrl2,
+                    to,
+                    t: 1.0,
+                });
+            }
+            b'Q' => {
+                let ctrl = self.point(relative)?;
+                let to = self.point(relative)?;
+                self.push(Segment::Quadratic { ctrl, to, t: 1.0 });
+            }
+            b'T' => {
+                let ctrl = self.cursor.smooth_quadratic_ctrl();
+                let to = self.point(relative)?;
+                self.push(Segment::Quadratic { ctrl, to, t: 1.0 });
+            }
+            b'A' => {
+                let radii = vector(self.number()?, self.number()?);
+                let x_rotation = Angle::degrees(self.number()?);
+                let flags = ArcFlags {
+                    large_arc: self.flag()?,
+                    sweep: self.flag()?,
+                };
+                let to = self.point(relative)?;
+                for segment in svg_arc(&self.cursor, radii, x_rotation, flags, to) {
+                    self.push(segment);
+                }
+            }
+            b'Z' => {
+                if self.cursor.open {
+                    self.push(Segment::End { close: true });
+                }
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+}
+
+/// Parses SVG path data into segments, starting from `cursor`.
+pub(super) fn parse(data: &str, cursor: Cursor) -> Result<Vec<Segment>, SvgPathError> {
+    let mut parser = Parser {
+        data: data.as_bytes(),
+        position: 0,
+        cursor,
+        segments: Vec::new(),
+    };
+    let mut previous: Option<u8> = None;
+    loop {
+        parser.skip_separators();
+        let Some(&character) = parser.data.get(parser.position) else {
+            break;
+        };
+        let command = match (character, previous) {
+            (
+                b'M' | b'm' | b'L' | b'l' | b'H' | b'h' | b'V' | b'v' | b'C' | b'c' | b'S' | b's'
+                | b'Q' | b'q' | b'T' | b't' | b'A' | b'a' | b'Z' | b'z',
+                _,
+            ) => {
+                parser.position += 1;
+                character
+            }
+            // Numbers repeat the previous command, except a close, which takes none. Numbers
+            // after a move are implicit lines.
+            (b'0'..=b'9' | b'-' | b'+' | b'.', Some(b'M')) => b'L',
+            (b'0'..=b'9' | b'-' | b'+' | b'.', Some(b'm')) => b'l',
+            (b'0'..=b'9' | b'-' | b'+' | b'.', Some(previous))
+                if !matches!(previous, b'Z' | b'z') =>
+            {
+                previous
+            }
+            _ => {
+                return Err(SvgPathError::UnexpectedCharacter {
+                    position: parser.position,
+                    character: data[parser.position..].chars().next().unwrap(),
+                })
+            }
+        };
+        if previous.is_none() && !matches!(command, b'M' | b'm') {
+            return Err(SvgPathError::MissingMoveTo);
+        }
+        parser.command(command)?;
+        previous = Some(command);
+    }
+    Ok(parser.segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(data: &str) -> Result<Vec<Segment>, SvgPathError> {
+        super::parse(data, Cursor::default())
+    }
+    fn line(x: f32, y: f32) -> Segment {
+        Segment::Line {
+            to: point(x, y),
+            t: 1.0,
+        }
+    }
+
+    #[test]
+    fn parses_absolute_and_relative_commands() {
+        assert_eq!(
+            parse("M10 10 L20 20 Z").unwrap(),
+            [
+                Segment::Begin(point(10.0, 10.0)),
+                line(20.0, 20.0),
+                Segment::End { close: true }
+            ]
+        );
+        // Coordinates after a move are lines, and `H`/`V` only change one axis.
+        assert_eq!(
+            parse("m10 10 20 0 0 20 h-5v-5H0V0z").unwrap(),
+            [
+                Segment::Begin(point(10.0, 10.0)),
+                line(30.0, 10.0),
+                line(30.0, 30.0),
+                line(25.0, 30.0),
+                line(25.0, 25.0),
+                line(0.0, 25.0),
+                line(0.0, 0.0),
+                Segment::End { close: true }
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_compact_numbers() {
+        assert_eq!(
+            parse("M.5.5L-1e1-2,3E0 4").unwrap(),
+            [
+                Segment::Begin(point(0.5, 0.5)),
+                line(-10.0, -2.0),
+                line(3.0, 4.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn reflects_smooth_control_points() {
+        let segments = parse("M0 0C0 10 10 10 10 0S20 -10 20 0Q25 5 30 0T40 0").unwrap();
+        assert!(matches!(
+            segments[2],
+            Segment::Cubic { ctrl1, .. } if ctrl1 == point(10.0, -10.0)
+        ));
+        assert!(matches!(
+            segments[4],
+            Segment::Quadratic { ctrl, .. } if ctrl == point(35.0, -5.0)
+        ));
+    }
+
+    #[test]
+    fn arcs_end_at_their_target() {
+        // The flags can be written without separators.
+        for data in ["M0 0A5 5 0 1 1 10 0", "M0 0a5,5,0,1110,0"] {
+            let segments = parse(data).unwrap();
+            assert!(segments.len() > 2);
+            assert!(matches!(
+                segments.last(),
+                Some(Segment::Quadratic { to, t, .. }) if *to == point(10.0, 0.0) && *t == 1.0
+            ));
+        }
+    }
+
+    #[test]
+    fn subpaths_restart_after_a_close() {
+        assert_eq!(
+            parse("M5 5L10 5ZL5 10").unwrap(),
+            [
+                Segment::Begin(point(5.0, 5.0)),
+                line(10.0, 5.0),
+                Segment::End { close: true },
+                Segment::Begin(point(5.0, 5.0)),
+                line(5.0, 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(parse("L10 10"), Err(SvgPathError::MissingMoveTo));
+        assert_eq!(
+            parse("M10"),
+            Err(SvgPathError::ExpectedNumber { position: 3 })
+        );
+        assert_eq!(
+            parse("M0 0 X"),
+            Err(SvgPathError::UnexpectedCharacter {
+                position: 5,
+                character: 'X'
+            })
+        );
+        assert_eq!(
+            parse("M0 0A1 1 0 2 0 1 1"),
+            Err(SvgPathError::ExpectedFlag { position: 11 })
+        );
+        assert_eq!(
+            parse("M0 0Z 1 1"),
+            Err(SvgPathError::UnexpectedCharacter {
+                position: 6,
+                character: '1'
+            })
+        );
+    }
+}
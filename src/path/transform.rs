@@ -0,0 +1,306 @@
+use lyon::{
+    geom::{CubicBezierSegment, QuadraticBezierSegment},
+    math::Point,
+    path::{path::BuilderWithAttributes, Event},
+};
+
+use super::{
+    segments::{Core, Segment},
+    ColorPath,
+};
+use crate::{
+    colors::LinearRgb,
+    math::{Point2D, Transform2D},
+};
+
+/// An endpoint of a `ColorPath`, with its color.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorPoint {
+    pub position: Point2D,
+    pub color: LinearRgb,
+}
+
+/// An event of a `ColorPath`. Endpoints carry their color; control points don't have one.
+pub type ColorPathEvent = Event<ColorPoint, Point2D>;
+
+/// A subpath of a `ColorPath` with its curves flattened to line segments.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polyline {
+    pub points: Vec<ColorPoint>,
+    /// Whether the last point connects back to the first.
+    pub closed: bool,
+}
+
+/// Linear-light attributes of an endpoint, as stored in the path.
+type Attributes = [f32; 4];
+
+fn attributes(attributes: &[f32]) -> Attributes {
+    [attributes[0], attributes[1], attributes[2], attributes[3]]
+}
+
+fn color_point(position: Point, attributes: Attributes) -> ColorPoint {
+    ColorPoint {
+        position: Point2D::from_untyped(position),
+        color: attributes.into(),
+    }
+}
+
+fn lerp(from: Attributes, to: Attributes, t: f32) -> Attributes {
+    [0, 1, 2, 3].map(|i| from[i] + (to[i] - from[i]) * t)
+}
+
+impl ColorPath {
+    /// A copy of the path with `transform` applied to every point.
+    pub fn transformed(&self, transform: Transform2D) -> ColorPath {
+        ColorPath(self.0.clone().transformed(&transform.to_untyped()))
+    }
+    /// Combines `paths` into one path containing all of their subpaths, in order.
+    pub fn merge<'a>(paths: impl IntoIterator<Item = &'a ColorPath>) -> ColorPath {
+        let slices = paths
+            .into_iter()
+            .map(|path| path.0.as_slice())
+            .collect::<Vec<_>>();
+        let mut builder = BuilderWithAttributes::new(4);
+        builder.extend_from_paths(&slices);
+        ColorPath(builder.build())
+    }
+    /// A copy of the path that runs backwards, keeping the colors at each point.
+    ///
+    /// Reversing a subpath flips its winding, so a reversed subpath merged into another can cut a
+    /// hole in it.
+    pub fn reversed(&self) -> ColorPath {
+        // Each subpath is its start followed by the segments leaving it, with their end colors.
+        let mut subpaths = Vec::new();
+        let mut segments = Vec::new();
+        for event in self.0.iter_with_attributes() {
+            let segment = match event {
+                Event::Begin { at } => (Segment::Begin(at.0), attributes(at.1)),
+                Event::Line { to, .. } => (Segment::Line { to: to.0, t: 1.0 }, attributes(to.1)),
+                Event::Quadratic { ctrl, to, .. } => (
+                    Segment::Quadratic {
+                        ctrl,
+                        to: to.0,
+                        t: 1.0,
+                    },
+                    attributes(to.1),
+                ),
+                Event::Cubic {
+                    ctrl1, ctrl2, to, ..
+                } => (
+                    Segment::Cubic {
+                        ctrl1,
+                        ctrl2,
+                        to: to.0,
+                        t: 1.0,
+                    },
+                    attributes(to.1),
+                ),
+                Event::End { close, .. } => {
+                    subpaths.push((std::mem::take(&mut segments), close));
+                    continue;
+                }
+            };
+            segments.push(segment);
+        }
+
+        let mut core = Core::new(4);
+        for (segments, close) in subpaths.into_iter().rev() {
+            let mut segments = segments.iter().rev();
+            let Some(&(mut segment, attributes)) = segments.next() else {
+                continue;
+            };
+            core.push(Segment::Begin(endpoint(&segment)), &attributes);
+            // Walk back over each segment to the end of the one before it.
+            for &(previous, attributes) in segments {
+                let to = endpoint(&previous);
+                let reversed = match segment {
+                    Segment::Quadratic { ctrl, .. } => Segment::Quadratic { ctrl, to, t: 1.0 },
+                    Segment::Cubic { ctrl1, ctrl2, .. } => Segment::Cubic {
+                        ctrl1: ctrl2,
+                        ctrl2: ctrl1,
+                        to,
+                        t: 1.0,
+                    },
+                    _ => Segment::Line { to, t: 1.0 },
+                };
+                core.push(reversed, &attributes);
+                segment = previous;
+            }
+            core.push(Segment::End { close }, &[]);
+        }
+        ColorPath(core.build())
+    }
+    /// The path's events, with the colors of their endpoints.
+    pub fn events(&self) -> impl Iterator<Item = ColorPathEvent> + '_ {
+        self.0.iter_with_attributes().map(|event| match event {
+            Event::Begin { at } => Event::Begin {
+                at: color_point(at.0, attributes(at.1)),
+            },
+            Event::Line { from, to } => Event::Line {
+                from: color_point(from.0, attributes(from.1)),
+                to: color_point(to.0, attributes(to.1)),
+            },
+            Event::Quadratic { from, ctrl, to } => Event::Quadratic {
+                from: color_point(from.0, attributes(from.1)),
+                ctrl: Point2D::from_untyped(ctrl),
+                to: color_point(to.0, attributes(to.1)),
+            },
+            Event::Cubic {
+                from,
+                ctrl1,
+                ctrl2,
+                to,
+            } => Event::Cubic {
+                from: color_point(from.0, attributes(from.1)),
+                ctrl1: Point2D::from_untyped(ctrl1),
+                ctrl2: Point2D::from_untyped(ctrl2),
+                to: color_point(to.0, attributes(to.1)),
+            },
+            Event::End { last, first, close } => Event::End {
+                last: color_point(last.0, attributes(last.1)),
+                first: color_point(first.0, attributes(first.1)),
+                close,
+            },
+        })
+    }
+    /// Flattens each subpath into line segments that stray at most `tolerance` from its curves.
+    /// Colors are interpolated along the curves.
+    pub fn flattened(&self, tolerance: f32) -> Vec<Polyline> {
+        let mut polylines = Vec::new();
+        let mut points = Vec::new();
+        for event in self.0.iter_with_attributes() {
+            match event {
+                Event::Begin { at } => {
+                    points.clear();
+                    points.push(color_point(at.0, attributes(at.1)));
+                }
+                Event::Line { to, .. } => points.push(color_point(to.0, attributes(to.1))),
+                Event::Quadratic { from, ctrl, to } => {
+                    let (from_attributes, to_attributes) = (attributes(from.1), attributes(to.1));
+                    QuadraticBezierSegment {
+                        from: from.0,
+                        ctrl,
+                        to: to.0,
+                    }
+                    .for_each_flattened_with_t(tolerance, &mut |line, t| {
+                        points.push(color_point(
+                            line.to,
+                            lerp(from_attributes, to_attributes, t.end),
+                        ));
+                    });
+                }
+                Event::Cubic {
+                    from,
+                    ctrl1,
+                    ctrl2,
+                    to,
+                } => {
+                    let (from_attributes, to_attributes) = (attributes(from.1), attributes(to.1));
+                    CubicBezierSegment {
+                        from: from.0,
+                        ctrl1,
+                        ctrl2,
+                        to: to.0,
+                    }
+                    .for_each_flattened_with_t(tolerance, &mut |line, t| {
+                        points.push(color_point(
+                            line.to,
+                            lerp(from_attributes, to_attributes, t.end),
+                        ));
+                    });
+                }
+                Event::End { close, .. } => polylines.push(Polyline {
+                    points: std::mem::take(&mut points),
+                    closed: close,
+                }),
+            }
+        }
+        polylines
+    }
+}
+
+/// Where `segment` ends.
+fn endpoint(segment: &Segment) -> Point {
+    match *segment {
+        Segment::Begin(to)
+        | Segment::Line { to, .. }
+        | Segment::Quadratic { to, .. }
+        | Segment::Cubic { to, .. } => to,
+        Segment::End { .. } => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colors::Color, math::Vector2D, path::ColorPathBuilder};
+
+    fn gradient_line() -> ColorPath {
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(Color::RED);
+        let mut builder = builder.begin(Point2D::new(0.0, 0.0));
+        builder.set_color(Color::BLUE);
+        builder
+            .quadratic_bezier_to(Point2D::new(5.0, 10.0), Point2D::new(10.0, 0.0))
+            .line_to(Point2D::new(20.0, 0.0))
+            .build()
+    }
+
+    #[test]
+    fn transforms_and_merges() {
+        let path = gradient_line();
+        let moved = path.transformed(Transform2D::translation(5.0, 1.0).then_scale(2.0, 2.0));
+        assert_eq!(
+            moved.bounding_box().min,
+            path.bounding_box().min + Vector2D::new(10.0, 2.0)
+        );
+
+        let merged = ColorPath::merge([&path, &moved]);
+        let begins = merged
+            .events()
+            .filter(|event| matches!(event, Event::Begin { .. }))
+            .count();
+        assert_eq!(begins, 2);
+        assert!((merged.length() - path.length() * 3.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn reverses_with_colors() {
+        let path = gradient_line();
+        let reversed = path.reversed();
+        let events = reversed.events().collect::<Vec<_>>();
+        assert!(matches!(
+            &events[0],
+            Event::Begin { at } if at.position == Point2D::new(20.0, 0.0) && at.color == LinearRgb::from(Color::BLUE)
+        ));
+        assert!(matches!(
+            &events[2],
+            Event::Quadratic { ctrl, to, .. }
+                if *ctrl == Point2D::new(5.0, 10.0) && to.color == LinearRgb::from(Color::RED)
+        ));
+        assert!((reversed.length() - path.length()).abs() < 1e-3);
+        assert_eq!(
+            reversed.reversed().0.iter().collect::<Vec<_>>(),
+            path.0.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn flattens_with_interpolated_colors() {
+        let polylines = gradient_line().flattened(0.1);
+        assert_eq!(polylines.len(), 1);
+        let points = &polylines[0].points;
+        assert!(!polylines[0].closed);
+        assert!(points.len() > 3);
+        assert_eq!(points[0].color, LinearRgb::from(Color::RED));
+        assert_eq!(points.last().unwrap().position, Point2D::new(20.0, 0.0));
+        // Points partway along the curve blend between the endpoint colors.
+        let middle = &points[points.len() / 2];
+        assert!(
+            middle.color != LinearRgb::from(Color::RED)
+                && middle.color != LinearRgb::from(Color::BLUE)
+        );
+    }
+}
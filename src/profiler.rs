@@ -0,0 +1,219 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    colors::Color,
+    math::{Box2D, Point2D},
+    rendering::{Canvas, RenderError, Renderer},
+    window::Waker,
+};
+
+/// How many frames a `Profiler` remembers.
+pub const HISTORY_LEN: usize = 120;
+
+/// What a `Renderer` did to draw its last frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub vertices: usize,
+    pub triangles: usize,
+    pub draw_calls: usize,
+    /// Time the GPU spent on the frame, if the renderer can measure it.
+    pub gpu_time: Option<Duration>,
+}
+
+/// Timings and counts for one frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Time spent in `Widget::update`.
+    pub update: Duration,
+    /// Time spent in `Widget::layout`.
+    pub layout: Duration,
+    /// Time spent in `Widget::render`, which includes tessellation.
+    pub render: Duration,
+    /// Time spent in `Renderer::render`, uploading buffers and submitting them.
+    pub submit: Duration,
+    pub renderer: RenderStats,
+}
+impl FrameStats {
+    /// The total time the CPU spent on the frame.
+    pub fn cpu_time(&self) -> Duration {
+        self.update + self.layout + self.render + self.submit
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProfilerState {
+    frames: VecDeque<FrameStats>,
+    overlay: bool,
+    waker: Waker,
+}
+
+/// A handle to the frame timings recorded by a `Window`.
+///
+/// Handles are cheap to clone and can be kept by widgets or other threads.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    state: Arc<Mutex<ProfilerState>>,
+}
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Records a frame, forgetting the oldest one if the history is full.
+    pub fn record(&self, frame: FrameStats) {
+        let mut state = self.state.lock().unwrap();
+        if state.frames.len() == HISTORY_LEN {
+            state.frames.pop_front();
+        }
+        state.frames.push_back(frame);
+    }
+    /// The most recent frame.
+    pub fn last_frame(&self) -> Option<FrameStats> {
+        self.state.lock().unwrap().frames.back().copied()
+    }
+    /// The recorded frames, oldest first.
+    pub fn frames(&self) -> Vec<FrameStats> {
+        self.state.lock().unwrap().frames.iter().copied().collect()
+    }
+    /// The average of the recorded frames. The GPU time is averaged over frames that have one.
+    pub fn average(&self) -> Option<FrameStats> {
+        let state = self.state.lock().unwrap();
+        let count = state.frames.len();
+        if count == 0 {
+            return None;
+        }
+        let mean_time = |time: fn(&FrameStats) -> Duration| {
+            state.frames.iter().map(time).sum::<Duration>() / count as u32
+        };
+        let mean_count = |count_of: fn(&FrameStats) -> usize| {
+            state.frames.iter().map(count_of).sum::<usize>() / count
+        };
+        let gpu_times = state
+            .frames
+            .iter()
+            .filter_map(|frame| frame.renderer.gpu_time)
+            .collect::<Vec<_>>();
+        Some(FrameStats {
+            update: mean_time(|frame| frame.update),
+            layout: mean_time(|frame| frame.layout),
+            render: mean_time(|frame| frame.render),
+            submit: mean_time(|frame| frame.submit),
+            renderer: RenderStats {
+                vertices: mean_count(|frame| frame.renderer.vertices),
+                triangles: mean_count(|frame| frame.renderer.triangles),
+                draw_calls: mean_count(|frame| frame.renderer.draw_calls),
+                gpu_time: (!gpu_times.is_empty())
+                    .then(|| gpu_times.iter().sum::<Duration>() / gpu_times.len() as u32),
+            },
+        })
+    }
+    /// Forgets all recorded frames.
+    pub fn clear(&self) {
+        self.state.lock().unwrap().frames.clear();
+    }
+    /// Show or hide the on-screen frame time graph, redrawing the window if it changed.
+    pub fn set_overlay(&self, overlay: bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.overlay != overlay {
+            state.overlay = overlay;
+            state.waker.wake();
+        }
+    }
+    pub fn overlay(&self) -> bool {
+        self.state.lock().unwrap().overlay
+    }
+    pub(crate) fn set_waker(&self, waker: Waker) {
+        self.state.lock().unwrap().waker = waker;
+    }
+    /// Draws a bar per recorded frame in the top-left corner of `canvas`, one pixel per 0.25ms,
+    /// with a line at 16.7ms. From the bottom, the segments are update (blue), layout (green),
+    /// render (yellow), submit (red), and GPU time (magenta).
+    pub(crate) fn draw_overlay<Renderer: self::Renderer>(
+        &self,
+        canvas: &mut Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        const BAR_WIDTH: f32 = 2.0;
+        const PIXELS_PER_MS: f32 = 4.0;
+        const HEIGHT: f32 = 100.0;
+        let frames = self.frames();
+        canvas.fill_rect(
+            Box2D::new(
+                Point2D::new(0.0, 0.0),
+                Point2D::new(HISTORY_LEN as f32 * BAR_WIDTH, HEIGHT),
+            ),
+            Color::rgba(0, 0, 0, 160),
+        )?;
+        for (i, frame) in frames.iter().enumerate() {
+            let x = i as f32 * BAR_WIDTH;
+            let mut bottom = HEIGHT;
+            for (duration, color) in [
+                (frame.update, Color::BLUE),
+                (frame.layout, Color::GREEN),
+                (frame.render, Color::YELLOW),
+                (frame.submit, Color::RED),
+                (frame.renderer.gpu_time.unwrap_or_default(), Color::MAGENTA),
+            ] {
+                let top = (bottom - duration.as_secs_f32() * 1000.0 * PIXELS_PER_MS).max(0.0);
+                if top < bottom {
+                    canvas.fill_rect(
+                        Box2D::new(Point2D::new(x, top), Point2D::new(x + BAR_WIDTH, bottom)),
+                        color,
+                    )?;
+                }
+                bottom = top;
+            }
+        }
+        let budget = HEIGHT - 1000.0 / 60.0 * PIXELS_PER_MS;
+        canvas.fill_rect(
+            Box2D::new(
+                Point2D::new(0.0, budget),
+                Point2D::new(HISTORY_LEN as f32 * BAR_WIDTH, budget + 1.0),
+            ),
+            Color::WHITE,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_bounded() {
+        let profiler = Profiler::new();
+        for i in 0..HISTORY_LEN + 10 {
+            profiler.record(FrameStats {
+                update: Duration::from_millis(i as u64),
+                ..Default::default()
+            });
+        }
+        let frames = profiler.frames();
+        assert_eq!(frames.len(), HISTORY_LEN);
+        assert_eq!(frames[0].update, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn average_skips_missing_gpu_times() {
+        let profiler = Profiler::new();
+        profiler.record(FrameStats {
+            submit: Duration::from_millis(2),
+            renderer: RenderStats {
+                draw_calls: 4,
+                gpu_time: Some(Duration::from_millis(3)),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        profiler.record(FrameStats {
+            submit: Duration::from_millis(4),
+            ..Default::default()
+        });
+        let average = profiler.average().unwrap();
+        assert_eq!(average.submit, Duration::from_millis(3));
+        assert_eq!(average.renderer.draw_calls, 2);
+        assert_eq!(average.renderer.gpu_time, Some(Duration::from_millis(3)));
+    }
+}
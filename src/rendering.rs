@@ -1,28 +1,52 @@
+use std::fmt::Display;
+
 use crate::{math::Box2D, widgets::Widget};
 use image::{ImageBuffer, Rgba};
-use log::debug;
+use log::{debug, warn};
 use lyon::lyon_tessellation::{
     BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator,
-    StrokeVertex, VertexBuffers,
+    StrokeVertex, TessellationError, VertexBuffers,
 };
 use raw_window_handle::HasRawWindowHandle;
 use winit::window::Window;
 
-use crate::{colors::Color, math::Size2D, path::ColorPath};
+use crate::{
+    colors::{Color, LinearRgb},
+    math::{PhysicalSize2D, ScaleFactor},
+    path::ColorPath,
+    profiler::RenderStats,
+    theme::Theme,
+};
 
+mod recording;
 mod wgpu;
+pub use self::recording::*;
 pub use self::wgpu::*;
+pub use ::wgpu::{Backends, VertexAttribute, VertexFormat};
 
 pub type Image = ImageBuffer<Rgba<f32>, Vec<f32>>;
 
+/// A vertex of a colored shape. `color` is linear-light RGB with straight alpha; see
+/// `Color::to_linear`.
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct ColorVertex {
     pub pos: [f32; 2],
     pub color: [f32; 4],
 }
 
+impl ColorVertex {
+    pub fn new(pos: [f32; 2], color: impl Into<LinearRgb>) -> Self {
+        Self {
+            pos,
+            color: color.into().to_array(),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct TextureVertex {
     pub pos: [f32; 2],
@@ -35,52 +59,456 @@ pub enum FaceType {
     Colored(Color),
 }
 
+/// An error produced while setting up a `Renderer` or drawing with it.
+#[derive(Debug)]
+pub enum RenderError {
+    /// No graphics adapter compatible with the window could be found.
+    NoAdapter,
+    /// The graphics device could not be created.
+    RequestDevice(::wgpu::RequestDeviceError),
+    /// The next surface texture could not be acquired.
+    Surface(::wgpu::SurfaceError),
+    /// A path could not be tessellated.
+    Tessellation(TessellationError),
+    /// A custom shader failed to compile or doesn't match its description.
+    InvalidShader(String),
+    /// The renderer doesn't support the requested operation.
+    Unsupported(String),
+}
+impl Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::NoAdapter => write!(f, "no compatible graphics adapter found"),
+            RenderError::RequestDevice(err) => write!(f, "failed to create device: {err}"),
+            RenderError::Surface(err) => write!(f, "failed to get surface texture: {err}"),
+            RenderError::Tessellation(err) => write!(f, "failed to tessellate path: {err}"),
+            RenderError::InvalidShader(err) => write!(f, "invalid shader: {err}"),
+            RenderError::Unsupported(what) => write!(f, "unsupported by renderer: {what}"),
+        }
+    }
+}
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::RequestDevice(err) => Some(err),
+            RenderError::Surface(err) => Some(err),
+            RenderError::Tessellation(err) => Some(err),
+            RenderError::NoAdapter
+            | RenderError::InvalidShader(_)
+            | RenderError::Unsupported(_) => None,
+        }
+    }
+}
+impl From<::wgpu::RequestDeviceError> for RenderError {
+    fn from(err: ::wgpu::RequestDeviceError) -> Self {
+        RenderError::RequestDevice(err)
+    }
+}
+impl From<::wgpu::SurfaceError> for RenderError {
+    fn from(err: ::wgpu::SurfaceError) -> Self {
+        RenderError::Surface(err)
+    }
+}
+impl From<TessellationError> for RenderError {
+    fn from(err: TessellationError) -> Self {
+        RenderError::Tessellation(err)
+    }
+}
+
+/// Which adapter to prefer when several are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerPreference {
+    /// Prefer an integrated GPU.
+    #[default]
+    LowPower,
+    /// Prefer a discrete GPU.
+    HighPerformance,
+}
+
+/// How rendered frames are presented to the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    /// Wait for vertical blank, never tearing. Always supported.
+    #[default]
+    Vsync,
+    /// Replace the queued frame with the newest one, never tearing but not blocking.
+    Mailbox,
+    /// Present immediately, possibly tearing.
+    Immediate,
+}
+
+/// The color encoding of the window surface.
+///
+/// Colors are always blended in linear light, so this doesn't change how frames look; renderers
+/// encode to non-sRGB surfaces themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SurfaceFormat {
+    /// Whatever format the surface prefers.
+    #[default]
+    Any,
+    /// An sRGB format, where the hardware encodes colors on write.
+    Srgb,
+    /// A non-sRGB format, where colors are written as the renderer encodes them.
+    Linear,
+    /// An `Rgba16Float` format holding linear, extended-range colors, so HDR colors above 1 reach
+    /// the display unclamped. Fails with `RenderError::Unsupported` where the surface can't use it.
+    Hdr,
+}
+
+/// How the window's alpha channel is composited with what's behind it.
+///
+/// Renderers always produce premultiplied colors, so `PreMultiplied` is preferred for
+/// transparent windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// `Opaque` for opaque windows, and a supported non-opaque mode for transparent ones.
+    #[default]
+    Auto,
+    /// Ignore alpha; the window is fully opaque.
+    Opaque,
+    /// Colors are premultiplied by alpha.
+    PreMultiplied,
+    /// Colors are not premultiplied; the compositor multiplies them.
+    PostMultiplied,
+    /// Use whatever the platform has been told elsewhere.
+    Inherit,
+}
+
+/// Options used to set up a `Renderer`.
+///
+/// Renderers should fail with `RenderError::Unsupported` rather than silently ignore an option
+/// that the adapter can't provide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RendererConfig {
+    /// The graphics APIs that may be used.
+    pub backends: Backends,
+    pub power_preference: PowerPreference,
+    pub present_mode: PresentMode,
+    pub surface_format: SurfaceFormat,
+    pub alpha_mode: AlphaMode,
+    /// Whether the window is transparent, so that undrawn regions should show what's behind it.
+    pub transparent: bool,
+    /// Measure how long the GPU spends on each frame, where supported. Timings are read back
+    /// without waiting, so they arrive a few frames late.
+    pub profile_gpu: bool,
+}
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            backends: Backends::all(),
+            power_preference: PowerPreference::default(),
+            present_mode: PresentMode::default(),
+            surface_format: SurfaceFormat::default(),
+            alpha_mode: AlphaMode::default(),
+            transparent: false,
+            profile_gpu: false,
+        }
+    }
+}
+
+/// A user-supplied WGSL shader with its own vertex layout and uniforms.
+///
+/// The shader needs `vertex` and `fragment` entry points, and should output premultiplied colors.
+/// The shared uniforms are available in group 0: the surface's `size: vec2<f32>` in physical
+/// pixels at binding 0 and `scale_factor: f32` at binding 1. If `uniform_size` isn't zero, the
+/// shader's own uniform buffer is bound at group 1, binding 0.
+#[derive(Debug, Clone)]
+pub struct CustomShader {
+    pub label: String,
+    pub source: String,
+    /// The size of one vertex, in bytes.
+    pub vertex_stride: u64,
+    pub vertex_attributes: Vec<VertexAttribute>,
+    /// The size of the shader's uniform buffer, in bytes.
+    pub uniform_size: u64,
+}
+impl CustomShader {
+    /// Checks that the source is valid WGSL with `vertex` and `fragment` entry points. Limits that
+    /// depend on the device are left to the renderer.
+    pub(crate) fn validate(&self) -> Result<(), RenderError> {
+        let module = naga::front::wgsl::parse_str(&self.source).map_err(|err| {
+            RenderError::InvalidShader(format!(
+                "{:?}: {}",
+                self.label,
+                err.emit_to_string(&self.source)
+            ))
+        })?;
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|err| RenderError::InvalidShader(format!("{:?}: {err}", self.label)))?;
+        for (name, stage) in [
+            ("vertex", naga::ShaderStage::Vertex),
+            ("fragment", naga::ShaderStage::Fragment),
+        ] {
+            if !module
+                .entry_points
+                .iter()
+                .any(|entry| entry.name == name && entry.stage == stage)
+            {
+                return Err(RenderError::InvalidShader(format!(
+                    "{:?} has no {name} entry point",
+                    self.label
+                )));
+            }
+        }
+        Ok(())
+    }
+    /// Checks that `object`'s vertices and uniforms fit this shader's layout.
+    pub(crate) fn check_object(&self, object: &CustomObject) -> Result<(), RenderError> {
+        if self.vertex_stride == 0 || object.vertices.len() as u64 % self.vertex_stride != 0 {
+            return Err(RenderError::InvalidShader(format!(
+                "{} bytes of vertices don't match the {} byte stride of {:?}",
+                object.vertices.len(),
+                self.vertex_stride,
+                self.label
+            )));
+        }
+        if object.uniforms.len() as u64 != self.uniform_size {
+            return Err(RenderError::InvalidShader(format!(
+                "{} bytes of uniforms don't match the {} bytes expected by {:?}",
+                object.uniforms.len(),
+                self.uniform_size,
+                self.label
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Identifies a shader registered with `Renderer::register_shader`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShaderId(pub u32);
+
+/// A shape drawn with a custom shader.
+#[derive(Debug, Clone)]
+pub struct CustomObject {
+    pub shader: ShaderId,
+    /// Raw vertex data, laid out as described by the shader's `vertex_attributes`.
+    pub vertices: Vec<u8>,
+    pub indices: Vec<u16>,
+    /// Raw uniform data, `uniform_size` bytes long.
+    pub uniforms: Vec<u8>,
+}
+
 /// A type that renders triangles and lines to a window.
 pub trait Renderer {
-    /// Render, clearing the render queue
-    fn render(&mut self);
-    /// Resize the window
-    fn resize(&mut self, new_size: Size2D, scale_factor: f32);
+    /// Render, clearing the render queue. Errors the renderer can recover from are logged
+    /// instead of returned.
+    fn render(&mut self) -> Result<(), RenderError>;
+    /// Resize the window's surface to `new_size` physical pixels, with `scale_factor` physical
+    /// pixels per logical pixel
+    fn resize(&mut self, new_size: PhysicalSize2D, scale_factor: ScaleFactor);
     /// Configure the renderer for the given window
-    fn new(window: &winit::window::Window) -> Self
+    fn new(window: &winit::window::Window, config: &RendererConfig) -> Result<Self, RenderError>
     where
         Self: Sized;
     /// Add a shape to the render queue
     fn add_colored_object(&mut self, vertices: VertexBuffers<ColorVertex, u16>);
     /// Add a textured shape to the render queue
-    fn add_textured_object(&mut self, vertices: VertexBuffers<TextureVertex, u16>);
+    fn add_textured_object(
+        &mut self,
+        vertices: VertexBuffers<TextureVertex, u16>,
+    ) -> Result<(), RenderError>;
     /// Register a texture. Returns the texture index.
     /// Implementors should be careful to ensure the texture isn't already registered.
-    fn register_texture(&mut self, texture: Image) -> u32;
+    fn register_texture(&mut self, texture: Image) -> Result<u32, RenderError>;
+    /// Register a custom shader. Returns the id to draw with.
+    fn register_shader(&mut self, _shader: CustomShader) -> Result<ShaderId, RenderError> {
+        Err(RenderError::Unsupported("custom shaders".into()))
+    }
+    /// Add a shape drawn with a custom shader to the render queue
+    fn add_custom_object(&mut self, _object: CustomObject) -> Result<(), RenderError> {
+        Err(RenderError::Unsupported("custom shaders".into()))
+    }
+    /// Statistics about the last rendered frame
+    fn stats(&self) -> RenderStats {
+        RenderStats::default()
+    }
+}
+
+/// A `Renderer` that forwards to a `dyn Renderer`, so code that is generic over renderers can be
+/// compiled once for all of them. See `Canvas::erased`.
+pub struct DynRenderer<'a>(pub &'a mut dyn Renderer);
+impl Renderer for DynRenderer<'_> {
+    fn render(&mut self) -> Result<(), RenderError> {
+        self.0.render()
+    }
+
+    fn resize(&mut self, new_size: PhysicalSize2D, scale_factor: ScaleFactor) {
+        self.0.resize(new_size, scale_factor)
+    }
+
+    /// Always fails, as a `DynRenderer` only wraps an existing renderer.
+    fn new(_window: &Window, _config: &RendererConfig) -> Result<Self, RenderError>
+    where
+        Self: Sized,
+    {
+        Err(RenderError::Unsupported(
+            "creating a DynRenderer for a window".into(),
+        ))
+    }
+
+    fn add_colored_object(&mut self, vertices: VertexBuffers<ColorVertex, u16>) {
+        self.0.add_colored_object(vertices)
+    }
+
+    fn add_textured_object(
+        &mut self,
+        vertices: VertexBuffers<TextureVertex, u16>,
+    ) -> Result<(), RenderError> {
+        self.0.add_textured_object(vertices)
+    }
+
+    fn register_texture(&mut self, texture: Image) -> Result<u32, RenderError> {
+        self.0.register_texture(texture)
+    }
+
+    fn register_shader(&mut self, shader: CustomShader) -> Result<ShaderId, RenderError> {
+        self.0.register_shader(shader)
+    }
+
+    fn add_custom_object(&mut self, object: CustomObject) -> Result<(), RenderError> {
+        self.0.add_custom_object(object)
+    }
+
+    fn stats(&self) -> RenderStats {
+        self.0.stats()
+    }
+}
+
+/// A `Renderer` that uses `Primary` if it can be created, and `Fallback` otherwise.
+///
+/// This is useful to keep an application running (e.g. on the `DebugRenderer` or a software
+/// renderer) on machines where the GPU path isn't available.
+pub enum FallbackRenderer<Primary, Fallback> {
+    Primary(Primary),
+    Fallback(Fallback),
+}
+impl<Primary: Renderer, Fallback: Renderer> Renderer for FallbackRenderer<Primary, Fallback> {
+    fn render(&mut self) -> Result<(), RenderError> {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.render(),
+            FallbackRenderer::Fallback(renderer) => renderer.render(),
+        }
+    }
+
+    fn resize(&mut self, new_size: PhysicalSize2D, scale_factor: ScaleFactor) {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.resize(new_size, scale_factor),
+            FallbackRenderer::Fallback(renderer) => renderer.resize(new_size, scale_factor),
+        }
+    }
+
+    fn new(window: &Window, config: &RendererConfig) -> Result<Self, RenderError>
+    where
+        Self: Sized,
+    {
+        match Primary::new(window, config) {
+            Ok(renderer) => Ok(FallbackRenderer::Primary(renderer)),
+            Err(err) => {
+                warn!("Failed to create primary renderer ({err}), using fallback");
+                Fallback::new(window, config).map(FallbackRenderer::Fallback)
+            }
+        }
+    }
+
+    fn add_colored_object(&mut self, vertices: VertexBuffers<ColorVertex, u16>) {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.add_colored_object(vertices),
+            FallbackRenderer::Fallback(renderer) => renderer.add_colored_object(vertices),
+        }
+    }
+
+    fn add_textured_object(
+        &mut self,
+        vertices: VertexBuffers<TextureVertex, u16>,
+    ) -> Result<(), RenderError> {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.add_textured_object(vertices),
+            FallbackRenderer::Fallback(renderer) => renderer.add_textured_object(vertices),
+        }
+    }
+
+    fn register_texture(&mut self, texture: Image) -> Result<u32, RenderError> {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.register_texture(texture),
+            FallbackRenderer::Fallback(renderer) => renderer.register_texture(texture),
+        }
+    }
+
+    fn register_shader(&mut self, shader: CustomShader) -> Result<ShaderId, RenderError> {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.register_shader(shader),
+            FallbackRenderer::Fallback(renderer) => renderer.register_shader(shader),
+        }
+    }
+
+    fn add_custom_object(&mut self, object: CustomObject) -> Result<(), RenderError> {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.add_custom_object(object),
+            FallbackRenderer::Fallback(renderer) => renderer.add_custom_object(object),
+        }
+    }
+
+    fn stats(&self) -> RenderStats {
+        match self {
+            FallbackRenderer::Primary(renderer) => renderer.stats(),
+            FallbackRenderer::Fallback(renderer) => renderer.stats(),
+        }
+    }
 }
 
 /// A `Renderer` that just `debug!`s everything.
 pub struct DebugRenderer {
     colored_buffer: VertexBuffers<ColorVertex, u16>,
+    textured_buffer: VertexBuffers<TextureVertex, u16>,
+    texture_count: u32,
+    shader_count: u32,
+    stats: RenderStats,
 }
 impl Renderer for DebugRenderer {
-    fn render(&mut self) {
+    fn render(&mut self) -> Result<(), RenderError> {
         debug!("Vertices: {:?}", self.colored_buffer.vertices);
         debug!("Indices: {:?}", self.colored_buffer.indices);
+        debug!("Textured vertices: {:?}", self.textured_buffer.vertices);
+        debug!("Textured indices: {:?}", self.textured_buffer.indices);
+        self.stats = RenderStats {
+            vertices: self.colored_buffer.vertices.len() + self.textured_buffer.vertices.len(),
+            triangles: (self.colored_buffer.indices.len() + self.textured_buffer.indices.len()) / 3,
+            draw_calls: 0,
+            gpu_time: None,
+        };
         self.colored_buffer.vertices.clear();
         self.colored_buffer.indices.clear();
+        self.textured_buffer.vertices.clear();
+        self.textured_buffer.indices.clear();
+        Ok(())
     }
 
-    fn resize(&mut self, new_size: Size2D, _scale_factor: f32) {
-        debug!("Resizing to {:?}", new_size);
+    fn resize(&mut self, new_size: PhysicalSize2D, scale_factor: ScaleFactor) {
+        debug!("Resizing to {:?} at scale {:?}", new_size, scale_factor);
     }
 
-    fn new(window: &Window) -> Self
+    fn new(window: &Window, config: &RendererConfig) -> Result<Self, RenderError>
     where
         Self: Sized,
     {
         debug!(
-            "Setting up debug renderer for window with handle {:?}",
-            window.raw_window_handle()
+            "Setting up debug renderer for window with handle {:?} and config {:?}",
+            window.raw_window_handle(),
+            config
         );
-        Self {
+        Ok(Self {
             colored_buffer: VertexBuffers::new(),
-        }
+            textured_buffer: VertexBuffers::new(),
+            texture_count: 0,
+            shader_count: 0,
+            stats: RenderStats::default(),
+        })
     }
 
     fn add_colored_object(&mut self, mut vertices: VertexBuffers<ColorVertex, u16>) {
@@ -92,141 +520,276 @@ impl Renderer for DebugRenderer {
         self.colored_buffer.indices.append(&mut vertices.indices);
     }
 
-    fn add_textured_object(&mut self, _vertices: VertexBuffers<TextureVertex, u16>) {
-        todo!()
+    fn add_textured_object(
+        &mut self,
+        mut vertices: VertexBuffers<TextureVertex, u16>,
+    ) -> Result<(), RenderError> {
+        debug!(
+            "Adding textured object with vertices: {:?} and indices: {:?}",
+            vertices.vertices, vertices.indices
+        );
+        self.textured_buffer.vertices.append(&mut vertices.vertices);
+        self.textured_buffer.indices.append(&mut vertices.indices);
+        Ok(())
     }
 
-    fn register_texture(&mut self, _texture: Image) -> u32 {
-        todo!()
+    fn register_texture(&mut self, texture: Image) -> Result<u32, RenderError> {
+        debug!(
+            "Registering {}x{} texture as {}",
+            texture.width(),
+            texture.height(),
+            self.texture_count
+        );
+        self.texture_count += 1;
+        Ok(self.texture_count - 1)
+    }
+
+    fn register_shader(&mut self, shader: CustomShader) -> Result<ShaderId, RenderError> {
+        debug!(
+            "Registering shader {:?} as {}",
+            shader.label, self.shader_count
+        );
+        self.shader_count += 1;
+        Ok(ShaderId(self.shader_count - 1))
+    }
+
+    fn add_custom_object(&mut self, object: CustomObject) -> Result<(), RenderError> {
+        debug!(
+            "Adding object with shader {:?}, {} bytes of vertices and indices: {:?}",
+            object.shader,
+            object.vertices.len(),
+            object.indices
+        );
+        Ok(())
+    }
+
+    fn stats(&self) -> RenderStats {
+        self.stats
     }
 }
 
 /// A type that outputs basic shapes to a given `Renderer`.
+///
+/// Tessellation tolerances are in physical pixels, so curves are flattened more finely on HiDPI
+/// displays.
 pub struct Canvas<'a, Renderer: self::Renderer> {
     renderer: &'a mut Renderer,
     pub(crate) bounds: Box2D,
+    scale_factor: ScaleFactor,
+    theme: &'a Theme,
 }
 impl<'a, Renderer: self::Renderer> Canvas<'a, Renderer> {
     /// Create a new canvas from a renderer.
-    pub(crate) fn new(renderer: &'a mut Renderer, bounds: Box2D) -> Self {
+    pub(crate) fn new(
+        renderer: &'a mut Renderer,
+        bounds: Box2D,
+        scale_factor: ScaleFactor,
+        theme: &'a Theme,
+    ) -> Self {
         Self {
             renderer,
             bounds,
+            scale_factor,
+            theme,
         }
     }
-    pub fn render<T: Widget>(&mut self, widget: &mut T, bounds: Box2D) {
-        widget.render(Canvas::new(self.renderer, bounds))
+    /// The area this canvas draws to, in window coordinates.
+    pub fn bounds(&self) -> Box2D {
+        self.bounds
+    }
+    /// The number of physical pixels per logical pixel on the window's current monitor.
+    pub fn scale_factor(&self) -> ScaleFactor {
+        self.scale_factor
+    }
+    /// The window's current theme.
+    pub fn theme(&self) -> &'a Theme {
+        self.theme
+    }
+    pub fn render<T: Widget>(&mut self, widget: &mut T, bounds: Box2D) -> Result<(), RenderError> {
+        widget.render(Canvas::new(
+            self.renderer,
+            bounds,
+            self.scale_factor,
+            self.theme,
+        ))
+    }
+    /// Calls `f` with this canvas drawing through a `DynRenderer`, for widgets that are rendered
+    /// without knowing the renderer's type.
+    pub fn erased<T>(&mut self, f: impl FnOnce(Canvas<'_, DynRenderer<'_>>) -> T) -> T {
+        let mut renderer = DynRenderer(&mut *self.renderer);
+        f(Canvas::new(
+            &mut renderer,
+            self.bounds,
+            self.scale_factor,
+            self.theme,
+        ))
+    }
+    fn fill_options(&self) -> FillOptions {
+        FillOptions::tolerance(FillOptions::DEFAULT_TOLERANCE / self.scale_factor.get())
+    }
+    fn stroke_options(&self, stroke: StrokeOptions) -> StrokeOptions {
+        stroke.with_tolerance(stroke.tolerance / self.scale_factor.get())
     }
     /// Draw a colored filled path.
-    pub fn fill_path(&mut self, path: ColorPath) {
+    pub fn fill_path(&mut self, path: ColorPath) -> Result<(), RenderError> {
         let mut geometry = VertexBuffers::new();
         let mut tesellator = FillTessellator::new();
         {
-            tesellator
-                .tessellate_path(
-                    &path.0,
-                    &FillOptions::default(),
-                    &mut BuffersBuilder::new(&mut geometry, |mut vertex: FillVertex| ColorVertex {
-                        pos: [
-                            (vertex.position().x.max(0.0) + self.bounds.min.x)
-                                .min(self.bounds.max.x),
-                            (vertex.position().y.max(0.0) + self.bounds.min.y)
-                                .min(self.bounds.max.y),
-                        ],
-                        color: [
-                            vertex.interpolated_attributes()[0],
-                            vertex.interpolated_attributes()[1],
-                            vertex.interpolated_attributes()[2],
-                            vertex.interpolated_attributes()[3],
-                        ],
-                    }),
-                )
-                .expect("Failed to tessellate path.");
+            tesellator.tessellate_path(
+                &path.0,
+                &self.fill_options(),
+                &mut BuffersBuilder::new(&mut geometry, |mut vertex: FillVertex| ColorVertex {
+                    pos: [
+                        (vertex.position().x.max(0.0) + self.bounds.min.x).min(self.bounds.max.x),
+                        (vertex.position().y.max(0.0) + self.bounds.min.y).min(self.bounds.max.y),
+                    ],
+                    color: [
+                        vertex.interpolated_attributes()[0],
+                        vertex.interpolated_attributes()[1],
+                        vertex.interpolated_attributes()[2],
+                        vertex.interpolated_attributes()[3],
+                    ],
+                }),
+            )?;
         }
         self.renderer.add_colored_object(geometry);
+        Ok(())
     }
     /// Draw a colored stroked path
-    pub fn stroke_path(&mut self, path: ColorPath, stroke: StrokeOptions) {
+    pub fn stroke_path(
+        &mut self,
+        path: ColorPath,
+        stroke: StrokeOptions,
+    ) -> Result<(), RenderError> {
         let mut geometry = VertexBuffers::new();
         let mut tesellator = StrokeTessellator::new();
         {
-            tesellator
-                .tessellate_path(
-                    &path.0,
-                    &stroke,
-                    &mut BuffersBuilder::new(&mut geometry, |mut vertex: StrokeVertex| {
-                        ColorVertex {
-                            pos: [
-                                (vertex.position().x.max(0.0) + self.bounds.min.x)
-                                    .min(self.bounds.max.x),
-                                (vertex.position().y.max(0.0) + self.bounds.min.y)
-                                    .min(self.bounds.max.y),
-                            ],
-                            color: [
-                                vertex.interpolated_attributes()[0],
-                                vertex.interpolated_attributes()[1],
-                                vertex.interpolated_attributes()[2],
-                                vertex.interpolated_attributes()[3],
-                            ],
-                        }
-                    }),
-                )
-                .expect("Failed to tessellate path.");
+            tesellator.tessellate_path(
+                &path.0,
+                &self.stroke_options(stroke),
+                &mut BuffersBuilder::new(&mut geometry, |mut vertex: StrokeVertex| ColorVertex {
+                    pos: [
+                        (vertex.position().x.max(0.0) + self.bounds.min.x).min(self.bounds.max.x),
+                        (vertex.position().y.max(0.0) + self.bounds.min.y).min(self.bounds.max.y),
+                    ],
+                    color: [
+                        vertex.interpolated_attributes()[0],
+                        vertex.interpolated_attributes()[1],
+                        vertex.interpolated_attributes()[2],
+                        vertex.interpolated_attributes()[3],
+                    ],
+                }),
+            )?;
         }
         self.renderer.add_colored_object(geometry);
+        Ok(())
     }
-    pub fn fill_rect(&mut self, rect: Box2D, color: Color) {
+    /// Fill a rectangle with a `Color` or an HDR `LinearRgb`.
+    pub fn fill_rect(
+        &mut self,
+        rect: Box2D,
+        color: impl Into<LinearRgb>,
+    ) -> Result<(), RenderError> {
+        let color = color.into().to_array();
         let mut geometry = VertexBuffers::new();
         let mut tesellator = FillTessellator::new();
         {
-            tesellator
-                .tessellate_rectangle(
-                    &rect,
-                    &FillOptions::default(),
-                    &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| ColorVertex {
-                        pos: [
-                            (vertex.position().x.max(0.0) + self.bounds.min.x)
-                                .min(self.bounds.max.x),
-                            (vertex.position().y.max(0.0) + self.bounds.min.y)
-                                .min(self.bounds.max.y),
-                        ],
-                        color: [
-                            color.r as f32 / 255.0,
-                            color.g as f32 / 255.0,
-                            color.b as f32 / 255.0,
-                            color.a as f32 / 255.0,
-                        ],
-                    }),
-                )
-                .expect("Failed to tessellate path.");
+            tesellator.tessellate_rectangle(
+                &rect.to_untyped(),
+                &self.fill_options(),
+                &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| ColorVertex {
+                    pos: [
+                        (vertex.position().x.max(0.0) + self.bounds.min.x).min(self.bounds.max.x),
+                        (vertex.position().y.max(0.0) + self.bounds.min.y).min(self.bounds.max.y),
+                    ],
+                    color,
+                }),
+            )?;
         }
         self.renderer.add_colored_object(geometry);
+        Ok(())
     }
-    pub fn stroke_rect(&mut self, rect: Box2D, color: Color, stroke: StrokeOptions) {
+    pub fn stroke_rect(
+        &mut self,
+        rect: Box2D,
+        color: impl Into<LinearRgb>,
+        stroke: StrokeOptions,
+    ) -> Result<(), RenderError> {
+        let color = color.into().to_array();
         let mut geometry = VertexBuffers::new();
         let mut tesellator = StrokeTessellator::new();
         {
-            tesellator
-                .tessellate_rectangle(
-                    &rect,
-                    &stroke,
-                    &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| ColorVertex {
-                        pos: [
-                            (vertex.position().x.max(0.0) + self.bounds.min.x)
-                                .min(self.bounds.max.x),
-                            (vertex.position().y.max(0.0) + self.bounds.min.y)
-                                .min(self.bounds.max.y),
-                        ],
-                        color: [
-                            color.r as f32 / 255.0,
-                            color.g as f32 / 255.0,
-                            color.b as f32 / 255.0,
-                            color.a as f32 / 255.0,
-                        ],
-                    }),
-                )
-                .expect("Failed to tessellate path.");
+            tesellator.tessellate_rectangle(
+                &rect.to_untyped(),
+                &self.stroke_options(stroke),
+                &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| ColorVertex {
+                    pos: [
+                        (vertex.position().x.max(0.0) + self.bounds.min.x).min(self.bounds.max.x),
+                        (vertex.position().y.max(0.0) + self.bounds.min.y).min(self.bounds.max.y),
+                    ],
+                    color,
+                }),
+            )?;
         }
         self.renderer.add_colored_object(geometry);
+        Ok(())
+    }
+    /// Register a custom shader with the renderer, so it can be used with `draw_custom`.
+    pub fn register_shader(&mut self, shader: CustomShader) -> Result<ShaderId, RenderError> {
+        self.renderer.register_shader(shader)
+    }
+    /// Draw a shape with a custom shader, in order with the rest of the canvas.
+    ///
+    /// The renderer can't see inside custom vertices, so they are neither offset nor clamped to the
+    /// canvas; use `bounds` to place them.
+    pub fn draw_custom<Vertex: bytemuck::Pod, Uniforms: bytemuck::Pod>(
+        &mut self,
+        shader: ShaderId,
+        vertices: &[Vertex],
+        indices: &[u16],
+        uniforms: &Uniforms,
+    ) -> Result<(), RenderError> {
+        self.renderer.add_custom_object(CustomObject {
+            shader,
+            vertices: bytemuck::cast_slice(vertices).to_vec(),
+            indices: indices.to_vec(),
+            uniforms: bytemuck::bytes_of(uniforms).to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{math::Point2D, path::ColorPathBuilder};
+
+    #[test]
+    fn curves_are_flattened_in_physical_pixels() {
+        let curve = || {
+            ColorPathBuilder::new()
+                .begin(Point2D::new(0.0, 0.0))
+                .cubic_bezier_to(
+                    Point2D::new(0.0, 100.0),
+                    Point2D::new(100.0, 100.0),
+                    Point2D::new(100.0, 0.0),
+                )
+                .end(true)
+                .build()
+        };
+        let bounds = Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(100.0, 100.0));
+        let mut triangles = Vec::new();
+        for scale_factor in [1.0, 3.0] {
+            let mut renderer = RecordingRenderer::new();
+            Canvas::new(
+                &mut renderer,
+                bounds,
+                ScaleFactor::new(scale_factor),
+                &Theme::default(),
+            )
+            .fill_path(curve())
+            .unwrap();
+            triangles.push(renderer.draws()[0].triangles);
+        }
+        assert!(triangles[1] > triangles[0]);
     }
 }
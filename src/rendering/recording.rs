@@ -0,0 +1,494 @@
+use std::fmt::Write;
+
+use lyon::lyon_tessellation::VertexBuffers;
+
+use super::{
+    Canvas, ColorVertex, CustomObject, CustomShader, Image, RenderError, Renderer, RendererConfig,
+    ShaderId, TextureVertex,
+};
+use crate::{
+    colors::{Color, LinearRgb},
+    math::{Box2D, PhysicalSize2D, Point2D, ScaleFactor},
+    profiler::RenderStats,
+    theme::Theme,
+    widgets::{BoxConstraints, Widget},
+};
+
+/// One object added to a `RecordingRenderer`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawCall {
+    /// The smallest box containing every vertex, in window coordinates.
+    pub bounds: Box2D,
+    pub vertices: usize,
+    pub triangles: usize,
+    pub kind: DrawKind,
+}
+impl DrawCall {
+    /// Whether the object lies entirely within `area`.
+    pub fn is_inside(&self, area: Box2D) -> bool {
+        area.contains_box(&self.bounds)
+    }
+    /// The object's average color, if it is a colored object.
+    pub fn color(&self) -> Option<&LinearRgb> {
+        match &self.kind {
+            DrawKind::Colored(color) => Some(color),
+            _ => None,
+        }
+    }
+    /// The index of the object's texture, if it is a textured object.
+    pub fn texture(&self) -> Option<u32> {
+        match self.kind {
+            DrawKind::Textured(texture) => Some(texture),
+            _ => None,
+        }
+    }
+}
+
+/// What kind of object a `DrawCall` drew.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawKind {
+    /// A colored object, with the average of its vertex colors in linear light.
+    Colored(LinearRgb),
+    /// A textured object, with the texture index of its first vertex.
+    Textured(u32),
+    /// An object drawn with a custom shader. Its vertices are opaque, so its bounds are empty.
+    Custom(ShaderId),
+}
+
+/// A `Renderer` that records what is drawn instead of drawing it, for testing widgets without a GPU
+/// or a window.
+///
+/// ```
+/// # use xenon::{colors::Color, math::*, rendering::RecordingRenderer, widgets::Rectangle};
+/// let mut renderer = RecordingRenderer::new();
+/// let mut rectangle = Rectangle {
+///     color: Color::RED.into(),
+///     max_size: Size2D::new(20.0, 20.0),
+/// };
+/// let bounds = Box2D::new(Point2D::new(10.0, 10.0), Point2D::new(50.0, 50.0));
+/// renderer.render_widget(&mut rectangle, bounds).unwrap();
+/// renderer.assert_drawn(Color::RED, bounds);
+/// ```
+#[derive(Debug)]
+pub struct RecordingRenderer {
+    draws: Vec<DrawCall>,
+    frame: Vec<DrawCall>,
+    textures: Vec<Image>,
+    shaders: Vec<CustomShader>,
+    size: PhysicalSize2D,
+    scale_factor: ScaleFactor,
+    stats: RenderStats,
+    theme: Theme,
+}
+impl Default for RecordingRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl RecordingRenderer {
+    pub fn new() -> Self {
+        Self {
+            draws: Vec::new(),
+            frame: Vec::new(),
+            textures: Vec::new(),
+            shaders: Vec::new(),
+            size: PhysicalSize2D::zero(),
+            scale_factor: ScaleFactor::identity(),
+            stats: RenderStats::default(),
+            theme: Theme::default(),
+        }
+    }
+    /// Sets the theme widgets are rendered with. The default is the light theme.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+    /// Lays `widget` out within `bounds`, then renders it at the top-left of `bounds` at the size
+    /// it picked, at the renderer's scale factor and with its theme.
+    pub fn render_widget<T: Widget>(
+        &mut self,
+        widget: &mut T,
+        bounds: Box2D,
+    ) -> Result<(), RenderError> {
+        let size = widget.layout(BoxConstraints::loose(bounds.size()));
+        let bounds = Box2D::new(bounds.min, bounds.min + size.to_vector());
+        let scale_factor = self.scale_factor;
+        let theme = self.theme.clone();
+        widget.render(Canvas::new(self, bounds, scale_factor, &theme))
+    }
+    /// The objects added since the last call to `render`, in order.
+    pub fn draws(&self) -> &[DrawCall] {
+        &self.draws
+    }
+    /// The objects drawn by the last call to `render`, in order.
+    pub fn frame(&self) -> &[DrawCall] {
+        &self.frame
+    }
+    /// Forgets the queued objects.
+    pub fn clear(&mut self) {
+        self.draws.clear();
+    }
+    /// The registered textures, by index.
+    pub fn textures(&self) -> &[Image] {
+        &self.textures
+    }
+    /// The registered shaders, by id.
+    pub fn shaders(&self) -> &[CustomShader] {
+        &self.shaders
+    }
+    /// The size the renderer was last resized to.
+    pub fn size(&self) -> PhysicalSize2D {
+        self.size
+    }
+    pub fn scale_factor(&self) -> ScaleFactor {
+        self.scale_factor
+    }
+    /// The first queued object matching `predicate`.
+    pub fn find(&self, predicate: impl Fn(&DrawCall) -> bool) -> Option<&DrawCall> {
+        self.draws.iter().find(|draw| predicate(draw))
+    }
+    /// Panics unless an object of `color` was queued entirely within `area`.
+    #[track_caller]
+    pub fn assert_drawn(&self, color: impl Into<LinearRgb>, area: Box2D) {
+        let color = color.into();
+        if self
+            .find(|draw| is_color(draw, color) && draw.is_inside(area))
+            .is_none()
+        {
+            panic!(
+                "nothing {} was drawn inside {:?}\n{}",
+                describe_color(color),
+                area,
+                self.describe()
+            );
+        }
+    }
+    /// Panics if any object of `color` was queued overlapping `area`.
+    #[track_caller]
+    pub fn assert_not_drawn(&self, color: impl Into<LinearRgb>, area: Box2D) {
+        let color = color.into();
+        if self
+            .find(|draw| is_color(draw, color) && draw.bounds.intersects(&area))
+            .is_some()
+        {
+            panic!(
+                "something {} was drawn over {:?}\n{}",
+                describe_color(color),
+                area,
+                self.describe()
+            );
+        }
+    }
+    /// Panics unless an object with texture `texture` was queued entirely within `area`.
+    #[track_caller]
+    pub fn assert_textured(&self, texture: u32, area: Box2D) {
+        if self
+            .find(|draw| draw.texture() == Some(texture) && draw.is_inside(area))
+            .is_none()
+        {
+            panic!(
+                "texture {} wasn't drawn inside {:?}\n{}",
+                texture,
+                area,
+                self.describe()
+            );
+        }
+    }
+    /// Panics unless exactly `count` objects were queued.
+    #[track_caller]
+    pub fn assert_draw_count(&self, count: usize) {
+        if self.draws.len() != count {
+            panic!(
+                "expected {} draw calls, got {}\n{}",
+                count,
+                self.draws.len(),
+                self.describe()
+            );
+        }
+    }
+    /// Lists the queued objects, one per line.
+    fn describe(&self) -> String {
+        let mut description = String::from("draw calls:");
+        for draw in &self.draws {
+            let kind = match &draw.kind {
+                DrawKind::Colored(color) => describe_color(*color),
+                DrawKind::Textured(texture) => format!("texture {texture}"),
+                DrawKind::Custom(shader) => format!("{shader:?}"),
+            };
+            write!(
+                description,
+                "\n  {} triangles of {} in {:?}",
+                draw.triangles, kind, draw.bounds
+            )
+            .unwrap();
+        }
+        description
+    }
+}
+impl Renderer for RecordingRenderer {
+    fn render(&mut self) -> Result<(), RenderError> {
+        self.stats = RenderStats {
+            vertices: self.draws.iter().map(|draw| draw.vertices).sum(),
+            triangles: self.draws.iter().map(|draw| draw.triangles).sum(),
+            draw_calls: self.draws.len(),
+            gpu_time: None,
+        };
+        self.frame = std::mem::take(&mut self.draws);
+        Ok(())
+    }
+
+    fn resize(&mut self, new_size: PhysicalSize2D, scale_factor: ScaleFactor) {
+        self.size = new_size;
+        self.scale_factor = scale_factor;
+    }
+
+    fn new(window: &winit::window::Window, _config: &RendererConfig) -> Result<Self, RenderError>
+    where
+        Self: Sized,
+    {
+        let size = window.inner_size();
+        let mut renderer = RecordingRenderer::new();
+        renderer.resize(
+            PhysicalSize2D::new(size.width, size.height),
+            ScaleFactor::new(window.scale_factor() as f32),
+        );
+        Ok(renderer)
+    }
+
+    fn add_colored_object(&mut self, vertices: VertexBuffers<ColorVertex, u16>) {
+        if vertices.indices.is_empty() {
+            return;
+        }
+        let mut color = [0.0; 4];
+        for vertex in &vertices.vertices {
+            for (sum, channel) in color.iter_mut().zip(vertex.color) {
+                *sum += channel;
+            }
+        }
+        let color = color.map(|sum| sum / vertices.vertices.len() as f32);
+        self.draws.push(DrawCall {
+            bounds: bounds(vertices.vertices.iter().map(|vertex| vertex.pos)),
+            vertices: vertices.vertices.len(),
+            triangles: vertices.indices.len() / 3,
+            kind: DrawKind::Colored(color.into()),
+        });
+    }
+
+    fn add_textured_object(
+        &mut self,
+        vertices: VertexBuffers<TextureVertex, u16>,
+    ) -> Result<(), RenderError> {
+        let Some(texture) = vertices.vertices.first().map(|vertex| vertex.tex_index) else {
+            return Ok(());
+        };
+        if texture as usize >= self.textures.len() {
+            return Err(RenderError::Unsupported(format!(
+                "texture {texture} isn't registered"
+            )));
+        }
+        self.draws.push(DrawCall {
+            bounds: bounds(vertices.vertices.iter().map(|vertex| vertex.pos)),
+            vertices: vertices.vertices.len(),
+            triangles: vertices.indices.len() / 3,
+            kind: DrawKind::Textured(texture),
+        });
+        Ok(())
+    }
+
+    fn register_texture(&mut self, texture: Image) -> Result<u32, RenderError> {
+        self.textures.push(texture);
+        Ok(self.textures.len() as u32 - 1)
+    }
+
+    fn register_shader(&mut self, shader: CustomShader) -> Result<ShaderId, RenderError> {
+        shader.validate()?;
+        self.shaders.push(shader);
+        Ok(ShaderId(self.shaders.len() as u32 - 1))
+    }
+
+    fn add_custom_object(&mut self, object: CustomObject) -> Result<(), RenderError> {
+        let shader = self.shaders.get(object.shader.0 as usize).ok_or_else(|| {
+            RenderError::InvalidShader(format!("{:?} isn't registered", object.shader))
+        })?;
+        shader.check_object(&object)?;
+        self.draws.push(DrawCall {
+            bounds: Box2D::zero(),
+            vertices: object.vertices.len() / shader.vertex_stride as usize,
+            triangles: object.indices.len() / 3,
+            kind: DrawKind::Custom(object.shader),
+        });
+        Ok(())
+    }
+
+    fn stats(&self) -> RenderStats {
+        self.stats
+    }
+}
+
+fn bounds(positions: impl Iterator<Item = [f32; 2]>) -> Box2D {
+    Box2D::from_points(positions.map(|[x, y]| Point2D::new(x, y)))
+}
+
+/// Whether `draw` is colored `color`, allowing for rounding in the average of its vertices.
+fn is_color(draw: &DrawCall, color: LinearRgb) -> bool {
+    draw.color().is_some_and(|drawn| {
+        drawn
+            .to_array()
+            .into_iter()
+            .zip(color.to_array())
+            .all(|(drawn, color)| (drawn - color).abs() < 1e-4)
+    })
+}
+
+/// `color` as hex if it fits in 8 bits, or as linear channels if it is out of range.
+fn describe_color(color: LinearRgb) -> String {
+    if color
+        .to_array()
+        .iter()
+        .all(|channel| (0.0..=1.0).contains(channel))
+    {
+        Color::from(color).to_hex()
+    } else {
+        format!("{:?}", color.to_array())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::Size2D,
+        path::ColorPathBuilder,
+        widgets::{RandomRectangle, Rectangle},
+    };
+
+    fn area(x0: f32, y0: f32, x1: f32, y1: f32) -> Box2D {
+        Box2D::new(Point2D::new(x0, y0), Point2D::new(x1, y1))
+    }
+
+    #[test]
+    fn records_colored_objects() {
+        let mut renderer = RecordingRenderer::new();
+        let mut rectangle = Rectangle {
+            color: Color::RED.into(),
+            max_size: Size2D::new(20.0, 30.0),
+        };
+        renderer
+            .render_widget(&mut rectangle, area(10.0, 10.0, 50.0, 50.0))
+            .unwrap();
+        renderer.assert_draw_count(1);
+        renderer.assert_drawn(Color::RED, area(10.0, 10.0, 50.0, 50.0));
+        renderer.assert_not_drawn(Color::RED, area(31.0, 0.0, 100.0, 100.0));
+        assert_eq!(renderer.draws()[0].bounds, area(10.0, 10.0, 30.0, 40.0));
+        assert_eq!(renderer.draws()[0].triangles, 2);
+
+        renderer.render().unwrap();
+        assert!(renderer.draws().is_empty());
+        assert_eq!(renderer.frame().len(), 1);
+        assert_eq!(renderer.stats().draw_calls, 1);
+        assert_eq!(renderer.stats().vertices, 4);
+    }
+
+    #[test]
+    fn averages_vertex_colors() {
+        let mut renderer = RecordingRenderer::new();
+        let theme = Theme::default();
+        let mut canvas = Canvas::new(
+            &mut renderer,
+            area(0.0, 0.0, 100.0, 100.0),
+            ScaleFactor::identity(),
+            &theme,
+        );
+        let mut builder = ColorPathBuilder::new();
+        builder.set_color(Color::WHITE);
+        let builder = builder.begin(Point2D::new(0.0, 0.0));
+        let mut builder = builder.line_to(Point2D::new(10.0, 0.0));
+        builder.set_color(Color::BLACK);
+        let path = builder
+            .line_to(Point2D::new(10.0, 10.0))
+            .line_to(Point2D::new(0.0, 10.0))
+            .end(true)
+            .build();
+        canvas.fill_path(path).unwrap();
+        // Half white and half black in linear light is lighter than #808080.
+        let color = *renderer.draws()[0].color().unwrap();
+        assert_eq!(Color::from(color), Color::rgb(0xbc, 0xbc, 0xbc));
+        assert!(color.r == color.g && color.g == color.b);
+    }
+
+    fn shader(source: &str) -> CustomShader {
+        CustomShader {
+            label: "Test shader".to_string(),
+            source: source.to_string(),
+            vertex_stride: 8,
+            vertex_attributes: Vec::new(),
+            uniform_size: 0,
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_shaders_and_unregistered_draws() {
+        let mut renderer = RecordingRenderer::new();
+        for source in [
+            "@vertex fn vertex( -> {",
+            "@vertex fn vertex() -> @builtin(position) vec4<f32> { return vec4<f32>(0.0); }",
+        ] {
+            assert!(matches!(
+                renderer.register_shader(shader(source)),
+                Err(RenderError::InvalidShader(_))
+            ));
+        }
+        let id = renderer
+            .register_shader(shader(
+                "@vertex fn vertex(@location(0) pos: vec2<f32>) -> @builtin(position) vec4<f32> {
+                    return vec4<f32>(pos, 0.0, 1.0);
+                }
+                @fragment fn fragment() -> @location(0) vec4<f32> {
+                    return vec4<f32>(1.0);
+                }",
+            ))
+            .unwrap();
+        assert_eq!(id, ShaderId(0));
+
+        let theme = Theme::default();
+        let mut canvas = Canvas::new(
+            &mut renderer,
+            area(0.0, 0.0, 10.0, 10.0),
+            ScaleFactor::identity(),
+            &theme,
+        );
+        let vertices = [[0.0f32, 0.0], [1.0, 0.0], [0.0, 1.0]];
+        let no_uniforms = [0u32; 0];
+        assert!(matches!(
+            canvas.draw_custom(ShaderId(1), &vertices, &[0, 1, 2], &no_uniforms),
+            Err(RenderError::InvalidShader(_))
+        ));
+        // Like the GPU renderer, draws must match the shader's vertex stride and uniform size.
+        assert!(matches!(
+            canvas.draw_custom(id, &[[0.0f32; 3]], &[0, 0, 0], &no_uniforms),
+            Err(RenderError::InvalidShader(_))
+        ));
+        assert!(matches!(
+            canvas.draw_custom(id, &vertices, &[0, 1, 2], &0u32),
+            Err(RenderError::InvalidShader(_))
+        ));
+        canvas
+            .draw_custom(id, &vertices, &[0, 1, 2], &no_uniforms)
+            .unwrap();
+        renderer.assert_draw_count(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "nothing #0000ffff was drawn")]
+    fn assertions_describe_the_draw_calls() {
+        let mut renderer = RecordingRenderer::new();
+        renderer
+            .render_widget(
+                &mut RandomRectangle::new(Size2D::new(10.0, 10.0)),
+                area(0.0, 0.0, 10.0, 10.0),
+            )
+            .unwrap();
+        renderer.assert_drawn(Color::BLUE, area(0.0, 0.0, 10.0, 10.0));
+    }
+}
@@ -1,63 +1,243 @@
-use log::debug;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::ops::Range;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use log::{debug, error, warn};
 use lyon::lyon_tessellation::VertexBuffers;
 use wgpu::util::DeviceExt;
 
-use crate::math::Size2D;
+use crate::{
+    math::{PhysicalSize2D, ScaleFactor},
+    profiler::RenderStats,
+};
 
-use super::{ColorVertex, Image, Renderer};
+use super::{
+    AlphaMode, ColorVertex, CustomObject, CustomShader, Image, PowerPreference, PresentMode,
+    RenderError, Renderer, RendererConfig, ShaderId, SurfaceFormat,
+};
 
 pub struct WgpuRenderer {
+    options: RendererConfig,
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
     surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
-    color_pipeline: wgpu::RenderPipeline,
-    size_buffer: wgpu::Buffer,
-    scale_factor_buffer: wgpu::Buffer,
-    uniform_bind_group: wgpu::BindGroup,
+    gpu: Gpu,
+    errors: Arc<DeviceErrors>,
+    textures: Vec<Image>,
+    shaders: Vec<CustomShader>,
     color_buffer: VertexBuffers<ColorVertex, u16>,
-    scale_factor: f32,
+    draws: Vec<Draw>,
+    stats: RenderStats,
+    scale_factor: ScaleFactor,
+}
+
+/// Errors the device reported outside of an error scope, collected for the next `render`.
+#[derive(Default)]
+struct DeviceErrors {
+    /// Set when the device is lost or runs out of memory, so it's recreated before the next frame.
+    needs_recovery: AtomicBool,
+}
+
+/// A queued draw call, kept in submission order.
+enum Draw {
+    Colored {
+        indices: Range<u32>,
+        base_vertex: i32,
+    },
+    Custom(CustomObject),
+}
+impl WgpuRenderer {
+    /// Recreates the device and everything that lives on it, re-uploading registered textures.
+    ///
+    /// This is called automatically when the device is lost, but can also be used to move
+    /// rendering to a new adapter after a GPU switch.
+    pub fn recover(&mut self) -> Result<(), RenderError> {
+        warn!("Recreating graphics device");
+        let (device, queue) = match request_device(&self.adapter, &self.options) {
+            Ok(device) => device,
+            Err(err) => {
+                warn!("Failed to recreate device on current adapter ({err}), requesting a new one");
+                self.adapter = request_adapter(&self.instance, &self.surface, &self.options)?;
+                request_device(&self.adapter, &self.options)?
+            }
+        };
+        // Build everything before replacing the old device, so a failure leaves the renderer as
+        // it was, ready to try again on the next frame.
+        let mut gpu = Gpu::new(
+            device,
+            queue,
+            &self.config,
+            self.scale_factor,
+            self.errors.clone(),
+        );
+        for texture in &self.textures {
+            gpu.add_texture(texture);
+        }
+        for shader in &self.shaders {
+            gpu.add_shader(shader)?;
+        }
+        self.gpu = gpu;
+        self.errors.needs_recovery.store(false, Ordering::SeqCst);
+        self.configure_surface();
+        Ok(())
+    }
+    fn clear_queue(&mut self) {
+        self.color_buffer.vertices.clear();
+        self.color_buffer.indices.clear();
+        self.draws.clear();
+    }
+    /// Configures the surface and the intermediate frame, unless the window is minimized.
+    fn configure_surface(&mut self) {
+        if self.config.width > 0 && self.config.height > 0 {
+            self.surface.configure(&self.gpu.device, &self.config);
+            self.gpu.resize_intermediate(&self.config);
+        }
+    }
+    /// Gets the next surface texture, reconfiguring the surface or recovering the device if needed.
+    /// Returns `None` if the frame should be skipped.
+    fn next_frame(&mut self) -> Result<Option<wgpu::SurfaceTexture>, RenderError> {
+        let mut recovered = false;
+        let mut reconfigured = false;
+        loop {
+            let err = match self.surface.get_current_texture() {
+                Ok(output) => return Ok(Some(output)),
+                Err(err) => err,
+            };
+            match surface_error_action(&err, reconfigured, recovered) {
+                SurfaceAction::Reconfigure => {
+                    debug!("Surface is {err:?}, reconfiguring");
+                    self.configure_surface();
+                    reconfigured = true;
+                }
+                SurfaceAction::Recover => {
+                    self.recover()?;
+                    recovered = true;
+                }
+                SurfaceAction::Skip => {
+                    warn!("Couldn't get a surface texture ({err}), skipping frame");
+                    return Ok(None);
+                }
+                SurfaceAction::Fail => return Err(err.into()),
+            }
+        }
+    }
+}
+
+/// What `next_frame` does about a failure to get the next surface texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SurfaceAction {
+    /// Reconfigure the surface and try again.
+    Reconfigure,
+    /// Recreate the device and try again.
+    Recover,
+    /// Give up on this frame but keep rendering.
+    Skip,
+    Fail,
 }
+
+/// Decides what to do about `err`, given what has already been tried this frame.
+fn surface_error_action(
+    err: &wgpu::SurfaceError,
+    reconfigured: bool,
+    recovered: bool,
+) -> SurfaceAction {
+    match err {
+        wgpu::SurfaceError::Timeout => SurfaceAction::Skip,
+        wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated if !reconfigured => {
+            SurfaceAction::Reconfigure
+        }
+        wgpu::SurfaceError::Lost if !recovered => SurfaceAction::Recover,
+        wgpu::SurfaceError::Outdated => SurfaceAction::Skip,
+        _ => SurfaceAction::Fail,
+    }
+}
+
 impl Renderer for WgpuRenderer {
-    fn render(&mut self) {
-        if self.color_buffer.vertices.is_empty() {
-            return;
+    /// Clears and presents a frame even when nothing was queued, so transparent windows don't
+    /// keep showing the previous one.
+    fn render(&mut self) -> Result<(), RenderError> {
+        if self.config.width == 0 || self.config.height == 0 {
+            debug!("Window is minimized, skipping frame");
+            self.clear_queue();
+            return Ok(());
         }
+        if self.errors.needs_recovery.load(Ordering::SeqCst) {
+            if let Err(err) = self.recover() {
+                self.clear_queue();
+                return Err(err);
+            }
+        }
+        let output = match self.next_frame()? {
+            Some(output) => output,
+            None => {
+                self.clear_queue();
+                return Ok(());
+            }
+        };
         debug!(
-            "Rendering {:?} vertices with {:?} indices",
-            self.color_buffer.vertices, self.color_buffer.indices
+            "Rendering {:?} vertices with {:?} indices in {} draws",
+            self.color_buffer.vertices,
+            self.color_buffer.indices,
+            self.draws.len()
         );
-        let color_vertex_buffer =
-            self.device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Color vertex buffer"),
-                    contents: bytemuck::cast_slice(self.color_buffer.vertices.as_slice()),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-        let color_index_buffer =
-            self.device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Color vertex buffer"),
-                    contents: bytemuck::cast_slice(self.color_buffer.indices.as_slice()),
-                    usage: wgpu::BufferUsages::INDEX,
-                });
-        let output = self
-            .surface
-            .get_current_texture()
-            .expect("Failed to get current texture");
+        let color_buffers = (!self.color_buffer.vertices.is_empty()).then(|| {
+            let vertex_buffer =
+                self.gpu
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Color vertex buffer"),
+                        contents: bytemuck::cast_slice(self.color_buffer.vertices.as_slice()),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+            let index_buffer =
+                self.gpu
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Color index buffer"),
+                        contents: bytemuck::cast_slice(self.color_buffer.indices.as_slice()),
+                        usage: wgpu::BufferUsages::INDEX,
+                    });
+            (vertex_buffer, index_buffer)
+        });
+        let custom_buffers = self
+            .draws
+            .iter()
+            .filter_map(|draw| match draw {
+                Draw::Custom(object) => Some(self.gpu.custom_buffers(object)),
+                Draw::Colored { .. } => None,
+            })
+            .collect::<Vec<_>>();
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self
+            .gpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render encoder"),
             });
+        let timestamp_buffer = self
+            .gpu
+            .timestamps
+            .as_ref()
+            .and_then(Timestamps::free_buffer);
+        if let (Some(timestamps), Some(_)) = (&self.gpu.timestamps, timestamp_buffer) {
+            encoder.write_timestamp(&timestamps.query_set, 0);
+        }
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Color render pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: self
+                        .gpu
+                        .intermediate
+                        .as_ref()
+                        .map_or(&view, |intermediate| &intermediate.view),
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -71,77 +251,444 @@ impl Renderer for WgpuRenderer {
                 })],
                 depth_stencil_attachment: None,
             });
-            render_pass.set_pipeline(&self.color_pipeline);
-            render_pass.set_vertex_buffer(0, color_vertex_buffer.slice(..));
-            render_pass.set_index_buffer(color_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-            render_pass.draw_indexed(0..self.color_buffer.indices.len() as u32, 0, 0..1);
+            render_pass.set_bind_group(0, &self.gpu.uniform_bind_group, &[]);
+            let mut custom_buffers = custom_buffers.iter();
+            for draw in &self.draws {
+                match draw {
+                    Draw::Colored {
+                        indices,
+                        base_vertex,
+                    } => {
+                        let (vertex_buffer, index_buffer) = color_buffers
+                            .as_ref()
+                            .expect("colored draws always have vertices");
+                        render_pass.set_pipeline(&self.gpu.color_pipeline);
+                        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                        render_pass
+                            .set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                        render_pass.draw_indexed(indices.clone(), *base_vertex, 0..1);
+                    }
+                    Draw::Custom(object) => {
+                        let (vertex_buffer, index_buffer, uniform_bind_group) = custom_buffers
+                            .next()
+                            .expect("every custom draw has buffers");
+                        render_pass
+                            .set_pipeline(&self.gpu.custom_pipelines[object.shader.0 as usize].0);
+                        if let Some(uniform_bind_group) = uniform_bind_group {
+                            render_pass.set_bind_group(1, uniform_bind_group, &[]);
+                        }
+                        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                        render_pass
+                            .set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                        render_pass.draw_indexed(0..object.indices.len() as u32, 0, 0..1);
+                    }
+                }
+            }
         }
-        self.queue.submit(std::iter::once(encoder.finish()));
+        if let (Some(blit_pipeline), Some(intermediate)) =
+            (&self.gpu.blit_pipeline, &self.gpu.intermediate)
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Blit render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&blit_pipeline.0);
+            render_pass.set_bind_group(0, &intermediate.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        if let (Some(timestamps), Some(index)) = (&self.gpu.timestamps, timestamp_buffer) {
+            encoder.write_timestamp(&timestamps.query_set, 1);
+            encoder.resolve_query_set(
+                &timestamps.query_set,
+                0..2,
+                &timestamps.read_buffers[index].buffer,
+                0,
+            );
+        }
+        self.gpu.queue.submit(std::iter::once(encoder.finish()));
         output.present();
-        self.color_buffer.vertices.clear();
-        self.color_buffer.indices.clear();
+        self.stats = RenderStats {
+            vertices: self.color_buffer.vertices.len()
+                + self
+                    .draws
+                    .iter()
+                    .map(|draw| match draw {
+                        Draw::Custom(object) => {
+                            object.vertices.len()
+                                / self.shaders[object.shader.0 as usize].vertex_stride as usize
+                        }
+                        Draw::Colored { .. } => 0,
+                    })
+                    .sum::<usize>(),
+            triangles: self
+                .draws
+                .iter()
+                .map(|draw| match draw {
+                    Draw::Colored { indices, .. } => indices.len() / 3,
+                    Draw::Custom(object) => object.indices.len() / 3,
+                })
+                .sum(),
+            draw_calls: self.draws.len(),
+            gpu_time: self.gpu.read_gpu_time(timestamp_buffer),
+        };
+        self.clear_queue();
+        Ok(())
     }
 
-    fn resize(&mut self, new_size: Size2D, scale_factor: f32) {
+    fn resize(&mut self, new_size: PhysicalSize2D, scale_factor: ScaleFactor) {
         self.scale_factor = scale_factor;
-        self.config.width = (new_size.width * self.scale_factor).round() as u32;
-        self.config.height = (new_size.height * self.scale_factor).round() as u32;
-        self.surface.configure(&self.device, &self.config);
-        self.queue.write_buffer(
-            &self.size_buffer,
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+        self.configure_surface();
+        self.gpu.queue.write_buffer(
+            &self.gpu.size_buffer,
             0,
-            bytemuck::cast_slice(&[new_size.width, new_size.height]),
+            bytemuck::cast_slice(&[new_size.width as f32, new_size.height as f32]),
         );
-        self.queue.write_buffer(
-            &self.scale_factor_buffer,
+        self.gpu.queue.write_buffer(
+            &self.gpu.scale_factor_buffer,
             0,
-            bytemuck::cast_slice(&[self.scale_factor]),
+            bytemuck::cast_slice(&[scale_factor.get()]),
         );
     }
 
-    fn new(window: &winit::window::Window) -> Self
+    fn new(window: &winit::window::Window, options: &RendererConfig) -> Result<Self, RenderError>
     where
         Self: Sized,
     {
         let size = window.inner_size();
-        let size = Size2D::new(size.width as f32, size.height as f32);
-        let scale_factor = window.scale_factor() as f32;
+        let scale_factor = ScaleFactor::new(window.scale_factor() as f32);
 
-        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let instance = wgpu::Instance::new(options.backends);
         let surface = unsafe { instance.create_surface(window) };
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::LowPower,
-            force_fallback_adapter: false,
-            compatible_surface: Some(&surface),
-        }))
-        .unwrap();
-        let (device, queue) = pollster::block_on(adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                label: None,
-                features: wgpu::Features::empty(),
-                limits: wgpu::Limits::default(),
-            },
-            None,
-        ))
-        .unwrap();
+        let adapter = request_adapter(&instance, &surface, options)?;
+        debug!("Using adapter {:?}", adapter.get_info());
+        let (device, queue) = request_device(&adapter, options)?;
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_supported_formats(&adapter)[0],
-            width: size.width as u32,
-            height: size.height as u32,
-            present_mode: wgpu::PresentMode::Fifo,
-            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            format: choose_format(&surface.get_supported_formats(&adapter), options)?,
+            width: size.width,
+            height: size.height,
+            present_mode: choose_present_mode(
+                &surface.get_supported_present_modes(&adapter),
+                options,
+            )?,
+            alpha_mode: choose_alpha_mode(&surface.get_supported_alpha_modes(&adapter), options)?,
+        };
+        let errors = Arc::new(DeviceErrors::default());
+        let gpu = Gpu::new(device, queue, &config, scale_factor, errors.clone());
+        let mut renderer = Self {
+            options: options.clone(),
+            instance,
+            adapter,
+            surface,
+            config,
+            gpu,
+            errors,
+            textures: Vec::new(),
+            shaders: Vec::new(),
+            color_buffer: VertexBuffers::new(),
+            draws: Vec::new(),
+            stats: RenderStats::default(),
+            scale_factor,
         };
-        surface.configure(&device, &config);
+        renderer.configure_surface();
+        Ok(renderer)
+    }
+
+    fn add_colored_object(&mut self, mut buffer: VertexBuffers<super::ColorVertex, u16>) {
+        if buffer.indices.is_empty() {
+            return;
+        }
+        let first_index = self.color_buffer.indices.len() as u32;
+        self.draws.push(Draw::Colored {
+            indices: first_index..first_index + buffer.indices.len() as u32,
+            base_vertex: self.color_buffer.vertices.len() as i32,
+        });
+        self.color_buffer.vertices.append(&mut buffer.vertices);
+        self.color_buffer.indices.append(&mut buffer.indices);
+    }
+
+    fn add_textured_object(
+        &mut self,
+        _vertices: VertexBuffers<super::TextureVertex, u16>,
+    ) -> Result<(), RenderError> {
+        Err(RenderError::Unsupported(
+            "textured objects are not yet supported".into(),
+        ))
+    }
+
+    fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    fn register_texture(&mut self, texture: Image) -> Result<u32, RenderError> {
+        self.gpu.add_texture(&texture);
+        self.textures.push(texture);
+        Ok(self.textures.len() as u32 - 1)
+    }
+
+    fn register_shader(&mut self, shader: CustomShader) -> Result<ShaderId, RenderError> {
+        shader.validate()?;
+        self.gpu.add_shader(&shader)?;
+        self.shaders.push(shader);
+        Ok(ShaderId(self.shaders.len() as u32 - 1))
+    }
+
+    fn add_custom_object(&mut self, object: CustomObject) -> Result<(), RenderError> {
+        let shader = self.shaders.get(object.shader.0 as usize).ok_or_else(|| {
+            RenderError::InvalidShader(format!("{:?} isn't registered", object.shader))
+        })?;
+        shader.check_object(&object)?;
+        if !object.indices.is_empty() {
+            self.draws.push(Draw::Custom(object));
+        }
+        Ok(())
+    }
+}
+
+fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: &wgpu::Surface,
+    options: &RendererConfig,
+) -> Result<wgpu::Adapter, RenderError> {
+    pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: match options.power_preference {
+            PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+            PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        },
+        force_fallback_adapter: false,
+        compatible_surface: Some(surface),
+    }))
+    .ok_or(RenderError::NoAdapter)
+}
+
+fn choose_format(
+    supported: &[wgpu::TextureFormat],
+    options: &RendererConfig,
+) -> Result<wgpu::TextureFormat, RenderError> {
+    supported
+        .iter()
+        .copied()
+        .find(|format| match options.surface_format {
+            SurfaceFormat::Any => true,
+            SurfaceFormat::Srgb => format.describe().srgb,
+            SurfaceFormat::Linear => !format.describe().srgb,
+            SurfaceFormat::Hdr => *format == HDR_FORMAT,
+        })
+        .ok_or_else(|| {
+            RenderError::Unsupported(format!(
+                "surface format {:?} (supported: {:?})",
+                options.surface_format, supported
+            ))
+        })
+}
+
+fn choose_present_mode(
+    supported: &[wgpu::PresentMode],
+    options: &RendererConfig,
+) -> Result<wgpu::PresentMode, RenderError> {
+    let present_mode = match options.present_mode {
+        PresentMode::Vsync => wgpu::PresentMode::Fifo,
+        PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+        PresentMode::Immediate => wgpu::PresentMode::Immediate,
+    };
+    if supported.contains(&present_mode) {
+        Ok(present_mode)
+    } else {
+        Err(RenderError::Unsupported(format!(
+            "present mode {:?} (supported: {:?})",
+            options.present_mode, supported
+        )))
+    }
+}
+
+fn choose_alpha_mode(
+    supported: &[wgpu::CompositeAlphaMode],
+    options: &RendererConfig,
+) -> Result<wgpu::CompositeAlphaMode, RenderError> {
+    let alpha_mode = match options.alpha_mode {
+        AlphaMode::Auto if !options.transparent => wgpu::CompositeAlphaMode::Opaque,
+        AlphaMode::Auto => {
+            return Ok([
+                wgpu::CompositeAlphaMode::PreMultiplied,
+                wgpu::CompositeAlphaMode::PostMultiplied,
+                wgpu::CompositeAlphaMode::Inherit,
+            ]
+            .into_iter()
+            .find(|mode| supported.contains(mode))
+            .unwrap_or_else(|| {
+                warn!("Surface doesn't support transparency, falling back to opaque");
+                wgpu::CompositeAlphaMode::Opaque
+            }));
+        }
+        AlphaMode::Opaque => wgpu::CompositeAlphaMode::Opaque,
+        AlphaMode::PreMultiplied => wgpu::CompositeAlphaMode::PreMultiplied,
+        AlphaMode::PostMultiplied => wgpu::CompositeAlphaMode::PostMultiplied,
+        AlphaMode::Inherit => wgpu::CompositeAlphaMode::Inherit,
+    };
+    if supported.contains(&alpha_mode) {
+        Ok(alpha_mode)
+    } else {
+        Err(RenderError::Unsupported(format!(
+            "alpha mode {:?} (supported: {:?})",
+            options.alpha_mode, supported
+        )))
+    }
+}
+
+fn request_device(
+    adapter: &wgpu::Adapter,
+    options: &RendererConfig,
+) -> Result<(wgpu::Device, wgpu::Queue), RenderError> {
+    let features = if options.profile_gpu {
+        adapter.features() & wgpu::Features::TIMESTAMP_QUERY
+    } else {
+        wgpu::Features::empty()
+    };
+    Ok(pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: None,
+            features,
+            limits: wgpu::Limits::default(),
+        },
+        None,
+    ))?)
+}
+
+fn upload_texture(device: &wgpu::Device, queue: &wgpu::Queue, image: &Image) -> wgpu::Texture {
+    let size = wgpu::Extent3d {
+        width: image.width(),
+        height: image.height(),
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Registered texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        bytemuck::cast_slice(image.as_raw()),
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: NonZeroU32::new(16 * image.width()),
+            rows_per_image: NonZeroU32::new(image.height()),
+        },
+        size,
+    );
+    texture
+}
+
+/// Everything that is owned by a `wgpu::Device`, and so has to be recreated when it's lost.
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    color_pipeline: wgpu::RenderPipeline,
+    size_buffer: wgpu::Buffer,
+    scale_factor_buffer: wgpu::Buffer,
+    uniform_bind_group_layout: wgpu::BindGroupLayout,
+    uniform_bind_group: wgpu::BindGroup,
+    // Not sampled until the textured pipeline exists, but kept resident so it survives recovery.
+    #[allow(dead_code)]
+    textures: Vec<wgpu::Texture>,
+    /// Pipelines for custom shaders, with the layout of their own uniforms if they have any.
+    custom_pipelines: Vec<(wgpu::RenderPipeline, Option<wgpu::BindGroupLayout>)>,
+    timestamps: Option<Timestamps>,
+    /// The format pipelines render to. Blending happens in linear light, so this is either an sRGB
+    /// surface format, the linear `HDR_FORMAT`, or `INTERMEDIATE_FORMAT`.
+    target_format: wgpu::TextureFormat,
+    /// Copies the intermediate frame to the surface, encoding it as sRGB.
+    blit_pipeline: Option<(wgpu::RenderPipeline, wgpu::BindGroupLayout)>,
+    intermediate: Option<Intermediate>,
+}
+
+/// The format of the linear frame used for surfaces that don't encode sRGB themselves.
+const INTERMEDIATE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// The surface format for `SurfaceFormat::Hdr`. It is already linear, so it's rendered to
+/// directly rather than through the sRGB-encoding blit.
+const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// The linear frame rendered into when the surface doesn't encode sRGB itself.
+struct Intermediate {
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+/// The size of the two timestamps written around each frame.
+const TIMESTAMP_BUFFER_SIZE: wgpu::BufferAddress = 2 * std::mem::size_of::<u64>() as u64;
+
+/// How many frames' timestamps can be waiting to be read back at once.
+const TIMESTAMP_BUFFER_COUNT: usize = 3;
+
+/// Queries used to time frames on the GPU.
+struct Timestamps {
+    query_set: wgpu::QuerySet,
+    /// Buffers the timestamps are resolved into. Each is read back once the GPU has finished with
+    /// it, a frame or more later, so reading never stalls the frame being drawn.
+    read_buffers: Vec<TimestampBuffer>,
+    /// The buffer the next frame's timestamps are resolved into.
+    next: usize,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+}
+impl Timestamps {
+    /// The buffer to resolve this frame's timestamps into, if it has been read since it was last
+    /// used. Frames aren't timed while every buffer is still waiting on the GPU.
+    fn free_buffer(&self) -> Option<usize> {
+        (!self.read_buffers[self.next].in_flight).then_some(self.next)
+    }
+}
+
+struct TimestampBuffer {
+    buffer: wgpu::Buffer,
+    /// Set by `map_async` once the buffer can be read.
+    mapped: Arc<AtomicBool>,
+    /// Whether the buffer holds timestamps that haven't been read yet.
+    in_flight: bool,
+}
+impl Gpu {
+    fn new(
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: &wgpu::SurfaceConfiguration,
+        scale_factor: ScaleFactor,
+        errors: Arc<DeviceErrors>,
+    ) -> Self {
+        // Nothing is fatal here: the renderer only fails if recreating the device does.
+        device.on_uncaptured_error(move |err| {
+            if needs_recovery(&err) {
+                error!("Graphics device lost or out of memory: {err}");
+                errors.needs_recovery.store(true, Ordering::SeqCst);
+            } else {
+                error!("Graphics device error: {err}");
+            }
+        });
         let size_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Size buffer"),
-            contents: bytemuck::cast_slice(&[size.width, size.height]),
+            contents: bytemuck::cast_slice(&[config.width as f32, config.height as f32]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
         let scale_factor_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Scale factor buffer"),
-            contents: bytemuck::cast_slice(&[scale_factor]),
+            contents: bytemuck::cast_slice(&[scale_factor.get()]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
         let uniform_bind_group_layout =
@@ -150,7 +697,7 @@ impl Renderer for WgpuRenderer {
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
                         binding: 0,
-                        visibility: wgpu::ShaderStages::VERTEX,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                         ty: wgpu::BindingType::Buffer {
                             ty: wgpu::BufferBindingType::Uniform,
                             has_dynamic_offset: false,
@@ -160,7 +707,7 @@ impl Renderer for WgpuRenderer {
                     },
                     wgpu::BindGroupLayoutEntry {
                         binding: 1,
-                        visibility: wgpu::ShaderStages::VERTEX,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                         ty: wgpu::BindingType::Buffer {
                             ty: wgpu::BufferBindingType::Uniform,
                             has_dynamic_offset: false,
@@ -184,6 +731,13 @@ impl Renderer for WgpuRenderer {
                 },
             ],
         });
+        let target_format = if config.format.describe().srgb || config.format == HDR_FORMAT {
+            config.format
+        } else {
+            INTERMEDIATE_FORMAT
+        };
+        let blit_pipeline =
+            (target_format != config.format).then(|| create_blit_pipeline(&device, config.format));
         let color_shader = device.create_shader_module(wgpu::include_wgsl!("wgpu/colored.wgsl"));
         let color_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -233,57 +787,464 @@ impl Renderer for WgpuRenderer {
                 module: &color_shader,
                 entry_point: "fragment",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
             multiview: None,
         });
+        let timestamps = device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+            .then(|| Timestamps {
+                query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("Frame timestamps"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count: 2,
+                }),
+                read_buffers: (0..TIMESTAMP_BUFFER_COUNT)
+                    .map(|_| TimestampBuffer {
+                        buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                            label: Some("Timestamp read buffer"),
+                            size: TIMESTAMP_BUFFER_SIZE,
+                            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                            mapped_at_creation: false,
+                        }),
+                        mapped: Arc::new(AtomicBool::new(false)),
+                        in_flight: false,
+                    })
+                    .collect(),
+                next: 0,
+                period: queue.get_timestamp_period(),
+            });
         Self {
-            surface,
             device,
             queue,
-            config,
             color_pipeline,
             size_buffer,
             scale_factor_buffer,
+            uniform_bind_group_layout,
             uniform_bind_group,
-            color_buffer: VertexBuffers::new(),
-            scale_factor,
+            textures: Vec::new(),
+            custom_pipelines: Vec::new(),
+            timestamps,
+            target_format,
+            blit_pipeline,
+            intermediate: None,
         }
     }
-
-    fn add_colored_object(&mut self, mut buffer: VertexBuffers<super::ColorVertex, u16>) {
-        self.color_buffer.vertices.append(&mut buffer.vertices);
-        self.color_buffer.indices.append(&mut buffer.indices);
+    /// Recreates the intermediate frame, if there is one, to match the surface.
+    fn resize_intermediate(&mut self, config: &wgpu::SurfaceConfiguration) {
+        let Some((_, blit_layout)) = &self.blit_pipeline else {
+            return;
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Intermediate frame"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: INTERMEDIATE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Intermediate frame bind group"),
+            layout: blit_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+        self.intermediate = Some(Intermediate { view, bind_group });
     }
-
-    fn add_textured_object(&mut self, _vertices: VertexBuffers<super::TextureVertex, u16>) {
-        todo!("Textured objects are not yet supported")
+    /// Starts reading back the timestamps resolved into `written` this frame, if any, and returns
+    /// the GPU time of the latest earlier frame that has finished since the last call.
+    fn read_gpu_time(&mut self, written: Option<usize>) -> Option<Duration> {
+        let timestamps = self.timestamps.as_mut()?;
+        if let Some(index) = written {
+            let read_buffer = &mut timestamps.read_buffers[index];
+            let mapped = read_buffer.mapped.clone();
+            read_buffer
+                .buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    if result.is_ok() {
+                        mapped.store(true, Ordering::Release);
+                    }
+                });
+            read_buffer.in_flight = true;
+            timestamps.next = (index + 1) % timestamps.read_buffers.len();
+        }
+        self.device.poll(wgpu::Maintain::Poll);
+        let count = timestamps.read_buffers.len();
+        let mut ticks = None;
+        // Oldest first, so the latest finished frame is the one reported.
+        for i in 0..count {
+            let read_buffer = &mut timestamps.read_buffers[(timestamps.next + i) % count];
+            if !read_buffer.in_flight || !read_buffer.mapped.swap(false, Ordering::Acquire) {
+                continue;
+            }
+            {
+                let data = read_buffer.buffer.slice(..).get_mapped_range();
+                let frame: &[u64] = bytemuck::cast_slice(&data);
+                ticks = Some(frame[1].saturating_sub(frame[0]));
+            }
+            read_buffer.buffer.unmap();
+            read_buffer.in_flight = false;
+        }
+        ticks.map(|ticks| Duration::from_nanos((ticks as f64 * timestamps.period as f64) as u64))
     }
+    fn add_texture(&mut self, image: &Image) {
+        self.textures
+            .push(upload_texture(&self.device, &self.queue, image));
+    }
+    fn add_shader(&mut self, shader: &CustomShader) -> Result<(), RenderError> {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(&shader.label),
+                source: wgpu::ShaderSource::Wgsl(shader.source.as_str().into()),
+            });
+        let uniform_layout = (shader.uniform_size > 0).then(|| {
+            self.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Custom uniform bind group layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: NonZeroU64::new(shader.uniform_size),
+                        },
+                        count: None,
+                    }],
+                })
+        });
+        let bind_group_layouts = std::iter::once(&self.uniform_bind_group_layout)
+            .chain(uniform_layout.as_ref())
+            .collect::<Vec<_>>();
+        let layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(&shader.label),
+                bind_group_layouts: &bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+        let pipeline = self
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&shader.label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "vertex",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: shader.vertex_stride,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &shader.vertex_attributes,
+                    }],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: "fragment",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: self.target_format,
+                        blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            });
+        if let Some(err) = pollster::block_on(self.device.pop_error_scope()) {
+            return Err(RenderError::InvalidShader(err.to_string()));
+        }
+        self.custom_pipelines.push((pipeline, uniform_layout));
+        Ok(())
+    }
+    /// Uploads the vertices, indices and uniforms of a custom draw.
+    fn custom_buffers(
+        &self,
+        object: &CustomObject,
+    ) -> (wgpu::Buffer, wgpu::Buffer, Option<wgpu::BindGroup>) {
+        let vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Custom vertex buffer"),
+                contents: &object.vertices,
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Custom index buffer"),
+                contents: bytemuck::cast_slice(&object.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        let uniform_bind_group = self.custom_pipelines[object.shader.0 as usize]
+            .1
+            .as_ref()
+            .map(|layout| {
+                let uniform_buffer =
+                    self.device
+                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Custom uniform buffer"),
+                            contents: &object.uniforms,
+                            usage: wgpu::BufferUsages::UNIFORM,
+                        });
+                self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Custom uniform bind group"),
+                    layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniform_buffer.as_entire_binding(),
+                    }],
+                })
+            });
+        (vertex_buffer, index_buffer, uniform_bind_group)
+    }
+}
+
+fn create_blit_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Blit bind group layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }],
+    });
+    let shader = device.create_shader_module(wgpu::include_wgsl!("wgpu/blit.wgsl"));
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Blit render pipeline layout"),
+        bind_group_layouts: &[&layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Blit render pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vertex",
+            buffers: &[],
+        },
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fragment",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+    });
+    (pipeline, layout)
+}
 
-    fn register_texture(&mut self, _texture: Image) -> u32 {
-        todo!("Textured objects are not yet supported")
+/// Returns `true` if the device has to be recreated after `err`: it was lost or ran out of
+/// memory. Other errors, like failed validations, only affect the call that caused them.
+fn needs_recovery(err: &wgpu::Error) -> bool {
+    matches!(err, wgpu::Error::OutOfMemory { .. }) || is_device_lost(err)
+}
+
+/// Returns `true` if the error was caused by the device being lost.
+///
+/// Like wgpu's own out-of-memory check, this looks for `DeviceError` among the error's sources.
+/// The queue errors forward their sources past a wrapped `DeviceError`, so they're checked too.
+fn is_device_lost(err: &wgpu::Error) -> bool {
+    use wgpu_core::device::{
+        queue::{QueueSubmitError, QueueWriteError},
+        DeviceError,
+    };
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(err) = source {
+        let device_error = err
+            .downcast_ref::<DeviceError>()
+            .or_else(|| match err.downcast_ref::<QueueSubmitError>() {
+                Some(QueueSubmitError::Queue(err)) => Some(err),
+                _ => None,
+            })
+            .or_else(|| match err.downcast_ref::<QueueWriteError>() {
+                Some(QueueWriteError::Queue(err)) => Some(err),
+                _ => None,
+            });
+        if matches!(device_error, Some(DeviceError::Lost)) {
+            return true;
+        }
+        source = err.source();
     }
+    false
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{colors::Color, widgets::Rectangle, window::Window};
+    use crate::{colors::Color, math::Size2D, widgets::Rectangle, window::Window};
 
     use super::*;
 
+    #[test]
+    fn choose_format_respects_srgb_preference() {
+        let supported = [
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+        ];
+        let options = |surface_format| RendererConfig {
+            surface_format,
+            ..Default::default()
+        };
+        assert_eq!(
+            choose_format(&supported, &options(SurfaceFormat::Any)).unwrap(),
+            wgpu::TextureFormat::Bgra8Unorm
+        );
+        assert_eq!(
+            choose_format(&supported, &options(SurfaceFormat::Srgb)).unwrap(),
+            wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        assert!(choose_format(&supported[..1], &options(SurfaceFormat::Srgb)).is_err());
+        assert!(choose_format(&supported, &options(SurfaceFormat::Hdr)).is_err());
+        let supported = [wgpu::TextureFormat::Bgra8Unorm, HDR_FORMAT];
+        assert_eq!(
+            choose_format(&supported, &options(SurfaceFormat::Hdr)).unwrap(),
+            HDR_FORMAT
+        );
+    }
+
+    #[test]
+    fn unsupported_present_and_alpha_modes_are_rejected() {
+        let options = RendererConfig {
+            present_mode: PresentMode::Mailbox,
+            alpha_mode: AlphaMode::PreMultiplied,
+            ..Default::default()
+        };
+        assert!(choose_present_mode(&[wgpu::PresentMode::Fifo], &options).is_err());
+        assert_eq!(
+            choose_present_mode(
+                &[wgpu::PresentMode::Fifo, wgpu::PresentMode::Mailbox],
+                &options
+            )
+            .unwrap(),
+            wgpu::PresentMode::Mailbox
+        );
+        assert!(choose_alpha_mode(&[wgpu::CompositeAlphaMode::Opaque], &options).is_err());
+    }
+
+    #[test]
+    fn transparent_windows_prefer_premultiplied_alpha() {
+        let options = RendererConfig {
+            transparent: true,
+            ..Default::default()
+        };
+        let supported = [
+            wgpu::CompositeAlphaMode::Opaque,
+            wgpu::CompositeAlphaMode::PostMultiplied,
+            wgpu::CompositeAlphaMode::PreMultiplied,
+        ];
+        assert_eq!(
+            choose_alpha_mode(&supported, &options).unwrap(),
+            wgpu::CompositeAlphaMode::PreMultiplied
+        );
+        assert_eq!(
+            choose_alpha_mode(&supported, &RendererConfig::default()).unwrap(),
+            wgpu::CompositeAlphaMode::Opaque
+        );
+    }
+
+    #[test]
+    fn surface_errors_reconfigure_then_recover() {
+        use wgpu::SurfaceError::*;
+        assert_eq!(
+            surface_error_action(&Timeout, false, false),
+            SurfaceAction::Skip
+        );
+        // Outdated surfaces just need reconfiguring, and are skipped if that doesn't help.
+        assert_eq!(
+            surface_error_action(&Outdated, false, false),
+            SurfaceAction::Reconfigure
+        );
+        assert_eq!(
+            surface_error_action(&Outdated, true, false),
+            SurfaceAction::Skip
+        );
+        // Lost surfaces are reconfigured first, then the device is recreated, then we give up.
+        assert_eq!(
+            surface_error_action(&Lost, false, false),
+            SurfaceAction::Reconfigure
+        );
+        assert_eq!(
+            surface_error_action(&Lost, true, false),
+            SurfaceAction::Recover
+        );
+        assert_eq!(surface_error_action(&Lost, true, true), SurfaceAction::Fail);
+        assert_eq!(
+            surface_error_action(&OutOfMemory, false, false),
+            SurfaceAction::Fail
+        );
+    }
+
+    #[test]
+    fn device_loss_is_told_apart_from_other_errors() {
+        use wgpu_core::device::{queue::QueueSubmitError, DeviceError};
+        let context = |cause: Box<dyn std::error::Error + Send + Sync>| wgpu::Error::Validation {
+            source: Box::new(wgpu_core::error::ContextError {
+                string: "Queue::submit",
+                cause,
+                label_key: "label",
+                label: String::new(),
+            }),
+            description: String::new(),
+        };
+        assert!(is_device_lost(&context(Box::new(DeviceError::Lost))));
+        assert!(is_device_lost(&context(Box::new(QueueSubmitError::Queue(
+            DeviceError::Lost
+        )))));
+        assert!(!is_device_lost(&context(Box::new(
+            DeviceError::OutOfMemory
+        ))));
+        assert!(!is_device_lost(&context(Box::new(
+            QueueSubmitError::Queue(DeviceError::Invalid)
+        ))));
+
+        // Only loss and running out of memory recreate the device; validation errors are logged.
+        assert!(needs_recovery(&context(Box::new(DeviceError::Lost))));
+        assert!(needs_recovery(&wgpu::Error::OutOfMemory {
+            source: Box::new(DeviceError::OutOfMemory),
+        }));
+        assert!(!needs_recovery(&context(Box::new(DeviceError::Invalid))));
+    }
+
     #[test]
     fn test_renderer() {
         env_logger::init();
         let window = Window::<WgpuRenderer, _>::new(
             "WGPU renderer test",
             Rectangle {
-                color: Color::WHITE,
+                color: Color::WHITE.into(),
                 max_size: Size2D::new(10.0, 10.0),
             },
-        );
+        )
+        .unwrap();
         window.run();
     }
 }
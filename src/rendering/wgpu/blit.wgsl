@@ -0,0 +1,25 @@
+// Copies the linear, premultiplied intermediate frame to a surface that doesn't encode sRGB itself.
+@group(0) @binding(0)
+var frame: texture_2d<f32>;
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // A single triangle covering the whole viewport.
+    var uv = vec2(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn linear_to_srgb(linear: vec3<f32>) -> vec3<f32> {
+    var low = linear * 12.92;
+    var high = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3(0.0031308));
+}
+
+@fragment
+fn fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    var color = textureLoad(frame, vec2<i32>(position.xy), 0);
+    if (color.a <= 0.0) {
+        return vec4(0.0);
+    }
+    return vec4(linear_to_srgb(color.rgb / color.a) * color.a, color.a);
+}
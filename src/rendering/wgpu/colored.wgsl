@@ -1,3 +1,4 @@
+// The surface size, in physical pixels.
 @group(0) @binding(0)
 var<uniform> size: vec2<f32>;
 @group(0) @binding(1)
@@ -16,14 +17,16 @@ struct VertexOutput {
 @vertex
 fn vertex(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
-    var physical_position = vec2(in.position.x, size.y - in.position.y) * scale_factor;
-    var wgpu_position = physical_position / (size / 2.0) - 1.0;
-    out.position = vec4(wgpu_position, 1.0, 1.0);
+    // Positions are in logical pixels from the top left.
+    let physical_position = in.position * scale_factor;
+    let wgpu_position = physical_position / size * 2.0 - 1.0;
+    out.position = vec4(wgpu_position.x, -wgpu_position.y, 1.0, 1.0);
     out.color = in.color;
     return out;
 }
 
+// Vertex colors have straight alpha; everything after the fragment shader is premultiplied.
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    return vec4(in.color.rgb * in.color.a, in.color.a);
 }
\ No newline at end of file
@@ -0,0 +1,429 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{colors::Color, window::Waker};
+
+/// Whether a theme is light or dark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThemeMode {
+    #[default]
+    Light,
+    Dark,
+}
+impl From<winit::window::Theme> for ThemeMode {
+    fn from(theme: winit::window::Theme) -> Self {
+        match theme {
+            winit::window::Theme::Light => ThemeMode::Light,
+            winit::window::Theme::Dark => ThemeMode::Dark,
+        }
+    }
+}
+
+/// The colors of a theme, named by what they are used for rather than what they look like.
+///
+/// Each `on_*` color is for text and icons drawn over the color it is named after.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    /// Behind everything else.
+    pub background: Color,
+    pub on_background: Color,
+    /// Cards, panels and other raised areas.
+    pub surface: Color,
+    pub on_surface: Color,
+    /// The main accent, for prominent controls.
+    pub primary: Color,
+    pub on_primary: Color,
+    /// A second accent, for less prominent controls.
+    pub secondary: Color,
+    pub on_secondary: Color,
+    pub error: Color,
+    pub on_error: Color,
+    /// Outlines and dividers.
+    pub border: Color,
+    /// Secondary text, such as hints and captions.
+    pub muted: Color,
+}
+impl Palette {
+    /// The color playing `role`.
+    pub fn get(&self, role: ColorRole) -> &Color {
+        match role {
+            ColorRole::Background => &self.background,
+            ColorRole::OnBackground => &self.on_background,
+            ColorRole::Surface => &self.surface,
+            ColorRole::OnSurface => &self.on_surface,
+            ColorRole::Primary => &self.primary,
+            ColorRole::OnPrimary => &self.on_primary,
+            ColorRole::Secondary => &self.secondary,
+            ColorRole::OnSecondary => &self.on_secondary,
+            ColorRole::Error => &self.error,
+            ColorRole::OnError => &self.on_error,
+            ColorRole::Border => &self.border,
+            ColorRole::Muted => &self.muted,
+        }
+    }
+}
+
+/// A color in a `Palette`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorRole {
+    Background,
+    OnBackground,
+    Surface,
+    OnSurface,
+    Primary,
+    OnPrimary,
+    Secondary,
+    OnSecondary,
+    Error,
+    OnError,
+    Border,
+    Muted,
+}
+
+/// A widget's color: either a role looked up in the current theme, or a fixed color.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThemeColor {
+    Role(ColorRole),
+    Fixed(Color),
+}
+impl Default for ThemeColor {
+    fn default() -> Self {
+        ThemeColor::Role(ColorRole::Primary)
+    }
+}
+impl ThemeColor {
+    /// The color to draw with under `theme`.
+    pub fn resolve(&self, theme: &Theme) -> Color {
+        match self {
+            ThemeColor::Role(role) => theme.palette.get(*role).clone(),
+            ThemeColor::Fixed(color) => color.clone(),
+        }
+    }
+}
+impl From<ColorRole> for ThemeColor {
+    fn from(role: ColorRole) -> Self {
+        ThemeColor::Role(role)
+    }
+}
+impl From<Color> for ThemeColor {
+    fn from(color: Color) -> Self {
+        ThemeColor::Fixed(color)
+    }
+}
+
+/// Font settings. Sizes are in logical pixels.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Typography {
+    pub font_family: String,
+    pub body_size: f32,
+    /// For captions and other secondary text.
+    pub small_size: f32,
+    pub heading_size: f32,
+    /// The distance between baselines, as a multiple of the font size.
+    pub line_height: f32,
+}
+impl Default for Typography {
+    fn default() -> Self {
+        Self {
+            font_family: "sans-serif".into(),
+            body_size: 14.0,
+            small_size: 12.0,
+            heading_size: 20.0,
+            line_height: 1.4,
+        }
+    }
+}
+
+/// Gaps between and inside widgets, smallest to largest, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spacing {
+    pub xs: f32,
+    pub sm: f32,
+    pub md: f32,
+    pub lg: f32,
+    pub xl: f32,
+}
+impl Default for Spacing {
+    fn default() -> Self {
+        Self {
+            xs: 2.0,
+            sm: 4.0,
+            md: 8.0,
+            lg: 16.0,
+            xl: 32.0,
+        }
+    }
+}
+
+/// Corner radii, smallest to largest, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Radii {
+    pub sm: f32,
+    pub md: f32,
+    pub lg: f32,
+}
+impl Default for Radii {
+    fn default() -> Self {
+        Self {
+            sm: 2.0,
+            md: 4.0,
+            lg: 8.0,
+        }
+    }
+}
+
+/// How widgets look: colors, fonts, spacing and corner radii.
+///
+/// The current theme is available from `Input::theme` during updates and `Canvas::theme` during
+/// rendering.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Theme {
+    pub mode: ThemeMode,
+    pub palette: Palette,
+    pub typography: Typography,
+    pub spacing: Spacing,
+    pub radii: Radii,
+}
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+impl Theme {
+    /// The built-in light theme.
+    pub fn light() -> Self {
+        Self {
+            mode: ThemeMode::Light,
+            palette: Palette {
+                background: Color::rgb(0xfa, 0xfa, 0xfa),
+                on_background: Color::rgb(0x1c, 0x1b, 0x1f),
+                surface: Color::WHITE,
+                on_surface: Color::rgb(0x1c, 0x1b, 0x1f),
+                primary: Color::rgb(0x25, 0x63, 0xeb),
+                on_primary: Color::WHITE,
+                secondary: Color::rgb(0x0f, 0x76, 0x6e),
+                on_secondary: Color::WHITE,
+                error: Color::rgb(0xb9, 0x1c, 0x1c),
+                on_error: Color::WHITE,
+                border: Color::rgb(0xd4, 0xd4, 0xd8),
+                muted: Color::rgb(0x71, 0x71, 0x7a),
+            },
+            typography: Typography::default(),
+            spacing: Spacing::default(),
+            radii: Radii::default(),
+        }
+    }
+    /// The built-in dark theme.
+    pub fn dark() -> Self {
+        Self {
+            mode: ThemeMode::Dark,
+            palette: Palette {
+                background: Color::rgb(0x12, 0x12, 0x14),
+                on_background: Color::rgb(0xe4, 0xe4, 0xe7),
+                surface: Color::rgb(0x1f, 0x1f, 0x23),
+                on_surface: Color::rgb(0xe4, 0xe4, 0xe7),
+                primary: Color::rgb(0x93, 0xc5, 0xfd),
+                on_primary: Color::rgb(0x0c, 0x1e, 0x3d),
+                secondary: Color::rgb(0x5e, 0xea, 0xd4),
+                on_secondary: Color::rgb(0x04, 0x2f, 0x2e),
+                error: Color::rgb(0xfc, 0xa5, 0xa5),
+                on_error: Color::rgb(0x45, 0x0a, 0x0a),
+                border: Color::rgb(0x3f, 0x3f, 0x46),
+                muted: Color::rgb(0xa1, 0xa1, 0xaa),
+            },
+            typography: Typography::default(),
+            spacing: Spacing::default(),
+            radii: Radii::default(),
+        }
+    }
+    /// The built-in theme for `mode`.
+    pub fn for_mode(mode: ThemeMode) -> Self {
+        match mode {
+            ThemeMode::Light => Self::light(),
+            ThemeMode::Dark => Self::dark(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ThemeState {
+    theme: Theme,
+    /// Use the built-in theme matching the system's mode, until a theme is set explicitly.
+    follow_system: bool,
+    system: ThemeMode,
+    changed: bool,
+    /// Wakes the window's event loop so changes made through a handle are drawn.
+    waker: Waker,
+}
+
+/// A handle to a `Window`'s theme, for switching it at runtime.
+///
+/// Handles are cheap to clone and can be kept by widgets or other threads. The window redraws
+/// with the new theme on its next update.
+#[derive(Debug, Clone)]
+pub struct ThemeHandle {
+    state: Arc<Mutex<ThemeState>>,
+}
+impl Default for ThemeHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ThemeHandle {
+    /// A handle that follows the system's light or dark mode.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ThemeState {
+                theme: Theme::default(),
+                follow_system: true,
+                system: ThemeMode::default(),
+                changed: false,
+                waker: Waker::default(),
+            })),
+        }
+    }
+    /// The current theme.
+    pub fn theme(&self) -> Theme {
+        self.state.lock().unwrap().theme.clone()
+    }
+    /// Switches to `theme`, and stops following the system's mode.
+    pub fn set_theme(&self, theme: Theme) {
+        let mut state = self.state.lock().unwrap();
+        state.follow_system = false;
+        state.theme = theme;
+        state.changed = true;
+        state.waker.wake();
+    }
+    /// Switches to the built-in theme for `mode`, and stops following the system's mode.
+    pub fn set_mode(&self, mode: ThemeMode) {
+        self.set_theme(Theme::for_mode(mode));
+    }
+    /// Whether the theme switches with the system's light or dark mode.
+    pub fn follows_system(&self) -> bool {
+        self.state.lock().unwrap().follow_system
+    }
+    /// Start or stop following the system's mode. When starting, switches to the built-in theme
+    /// for the current system mode.
+    pub fn set_follow_system(&self, follow_system: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.follow_system = follow_system;
+        if follow_system {
+            let system = state.system;
+            state.use_system(system);
+            if state.changed {
+                state.waker.wake();
+            }
+        }
+    }
+    /// Records the system's mode, switching to its built-in theme if following the system.
+    pub(crate) fn system_changed(&self, mode: ThemeMode) {
+        let mut state = self.state.lock().unwrap();
+        state.system = mode;
+        if state.follow_system {
+            state.use_system(mode);
+        }
+    }
+    pub(crate) fn set_waker(&self, waker: Waker) {
+        self.state.lock().unwrap().waker = waker;
+    }
+    /// The new theme, if it changed since the last call.
+    pub(crate) fn take_changed(&self) -> Option<Theme> {
+        let mut state = self.state.lock().unwrap();
+        std::mem::take(&mut state.changed).then(|| state.theme.clone())
+    }
+}
+impl ThemeState {
+    fn use_system(&mut self, mode: ThemeMode) {
+        let theme = Theme::for_mode(mode);
+        if self.theme != theme {
+            self.theme = theme;
+            self.changed = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_palettes_are_readable() {
+        for theme in [Theme::light(), Theme::dark()] {
+            let palette = &theme.palette;
+            for (background, foreground) in [
+                (&palette.background, &palette.on_background),
+                (&palette.surface, &palette.on_surface),
+                (&palette.primary, &palette.on_primary),
+                (&palette.secondary, &palette.on_secondary),
+                (&palette.error, &palette.on_error),
+                (&palette.background, &palette.muted),
+            ] {
+                assert!(
+                    background.contrast_ratio(foreground) >= 4.5,
+                    "{foreground} on {background} in {:?}",
+                    theme.mode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn theme_colors_resolve_against_the_theme() {
+        let color = ThemeColor::from(ColorRole::Surface);
+        assert_eq!(color.resolve(&Theme::light()), Color::WHITE);
+        assert_eq!(color.resolve(&Theme::dark()), Theme::dark().palette.surface);
+        assert_eq!(
+            ThemeColor::from(Color::RED).resolve(&Theme::dark()),
+            Color::RED
+        );
+    }
+
+    #[test]
+    fn rectangles_follow_the_theme() {
+        use crate::{
+            math::{Box2D, Point2D, Size2D},
+            rendering::RecordingRenderer,
+            widgets::Rectangle,
+        };
+        let mut rectangle = Rectangle {
+            color: ColorRole::Surface.into(),
+            max_size: Size2D::new(10.0, 10.0),
+        };
+        let bounds = Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(10.0, 10.0));
+        let mut renderer = RecordingRenderer::new();
+        renderer.render_widget(&mut rectangle, bounds).unwrap();
+        renderer.set_theme(Theme::dark());
+        renderer.render_widget(&mut rectangle, bounds).unwrap();
+        renderer.assert_drawn(Color::WHITE, bounds);
+        renderer.assert_drawn(Theme::dark().palette.surface, bounds);
+    }
+
+    #[test]
+    fn handles_follow_the_system_until_set() {
+        let handle = ThemeHandle::new();
+        assert_eq!(handle.take_changed(), None);
+        handle.system_changed(ThemeMode::Dark);
+        assert_eq!(handle.take_changed(), Some(Theme::dark()));
+        assert_eq!(handle.take_changed(), None);
+        handle.system_changed(ThemeMode::Dark);
+        assert_eq!(handle.take_changed(), None);
+
+        let mut custom = Theme::light();
+        custom.spacing.md = 12.0;
+        handle.clone().set_theme(custom.clone());
+        handle.system_changed(ThemeMode::Light);
+        assert!(!handle.follows_system());
+        assert_eq!(handle.take_changed(), Some(custom));
+
+        // Following the system again drops the custom theme.
+        handle.set_follow_system(true);
+        assert_eq!(handle.take_changed(), Some(Theme::light()));
+    }
+}
@@ -1,48 +1,107 @@
 use crate::input::{MouseButton, VirtualKeyCode};
 
 use crate::colors::Color;
-use crate::math::{Point2D, Size2D};
+use crate::math::{Point2D, ScaleFactor, Size2D};
+use crate::rendering::RenderError;
+use crate::theme::{Theme, ThemeColor};
+
+mod boxed;
+mod constraints;
+mod flex;
+mod layout;
+pub use self::boxed::*;
+pub use self::constraints::*;
+pub use self::flex::*;
+pub use self::layout::*;
 use crate::{input::Input, math::Box2D, rendering::Canvas};
 
 pub trait Widget {
-    fn size(&self, max: Size2D) -> Size2D { max }
-    fn resize(&self, new_size: Size2D) -> Size2D { new_size }
+    /// Picks the widget's size within `constraints`, and lays out any children. Called before
+    /// `render` whenever the widget or its constraints may have changed.
+    ///
+    /// The default takes the largest size allowed, or the smallest in unbounded dimensions.
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        constraints.biggest()
+    }
+    /// The narrowest the widget can be without clipping its content, given `height`. For text,
+    /// this is the width of its longest word.
+    fn min_intrinsic_width(&self, _height: f32) -> f32 {
+        0.0
+    }
+    /// The narrowest width beyond which more width wouldn't make the widget any shorter, given
+    /// `height`. For text, this is the width of its longest line without wrapping.
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        self.min_intrinsic_width(height)
+    }
+    /// The shortest the widget can be without clipping its content, given `width`.
+    fn min_intrinsic_height(&self, _width: f32) -> f32 {
+        0.0
+    }
+    /// The shortest height beyond which more height wouldn't change the widget, given `width`.
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        self.min_intrinsic_height(width)
+    }
+    /// Draws the widget into `canvas`, whose bounds are the size it was laid out at.
+    ///
+    /// This is generic so rendering is statically dispatched; `DynWidget::render_dyn` renders
+    /// widgets whose type isn't known.
     fn render<Renderer: crate::rendering::Renderer>(
-            &mut self,
-            canvas: Canvas<'_, Renderer>,
-            );
-    fn update(&mut self, _input: &Input) -> bool { false }
+        &mut self,
+        canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError>
+    where
+        Self: Sized;
+    fn update(&mut self, _input: &Input) -> bool {
+        false
+    }
     fn focus(&mut self) -> bool {
         false
     }
+    /// Called when the window moves to a monitor with a different scale factor, before it is laid
+    /// out and rendered again. Widgets that cache anything tied to physical pixels, such as
+    /// tessellated geometry or rasterized glyphs, should drop it here.
+    fn scale_factor_changed(&mut self, _scale_factor: ScaleFactor) {}
+    /// Boxes the widget, so it can be stored alongside widgets of other types.
+    fn boxed(self) -> BoxedWidget
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(self)
+    }
+    /// Called when the window's theme changes, before it is laid out and rendered again with the
+    /// new theme. Widgets that cache anything derived from the theme should update it here.
+    fn theme_changed(&mut self, _theme: &Theme) {}
 }
 
 #[derive(Default)]
 pub struct Rectangle {
-    pub color: Color,
+    /// A palette role, so the rectangle follows theme changes, or a fixed color.
+    pub color: ThemeColor,
     pub max_size: Size2D,
 }
 impl Widget for Rectangle {
-    fn size(&self, _max: Size2D) -> Size2D {
-        self.max_size
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        constraints.constrain(self.max_size)
+    }
+    fn min_intrinsic_width(&self, _height: f32) -> f32 {
+        self.max_size.width
     }
-    fn resize(&self, _new_size: Size2D) -> Size2D {
-        self.max_size
+    fn min_intrinsic_height(&self, _width: f32) -> f32 {
+        self.max_size.height
     }
     fn render<Renderer: crate::rendering::Renderer>(
         &mut self,
         mut canvas: Canvas<'_, Renderer>,
-    ) {
+    ) -> Result<(), RenderError> {
+        // Fill the size the rectangle was laid out at, which may be stretched or squeezed.
+        let size = canvas.bounds().size();
         canvas.fill_rect(
             Box2D::new(
                 Point2D::new(0.0, 0.0),
-                Point2D::new(
-                    self.max_size.width,
-                    self.max_size.height,
-                ),
+                Point2D::new(size.width, size.height),
             ),
-            self.color.clone(),
-        );
+            self.color.resolve(canvas.theme()),
+        )
     }
 }
 
@@ -61,7 +120,8 @@ impl RandomRectangle {
                     fastrand::u8(0..255),
                     fastrand::u8(0..255),
                     255,
-                ),
+                )
+                .into(),
                 max_size: Size2D::new(
                     fastrand::f32() * max_size.width,
                     fastrand::f32() * max_size.height,
@@ -72,17 +132,20 @@ impl RandomRectangle {
     }
 }
 impl Widget for RandomRectangle {
-    fn size(&self, _max: Size2D) -> Size2D {
-        self.rectangle.max_size
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        self.rectangle.layout(constraints)
     }
-    fn resize(&self, _new_size: Size2D) -> Size2D {
-        self.rectangle.max_size
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        self.rectangle.min_intrinsic_width(height)
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        self.rectangle.min_intrinsic_height(width)
     }
     fn render<Renderer: crate::rendering::Renderer>(
         &mut self,
         canvas: Canvas<'_, Renderer>,
-    ) {
-        self.rectangle.render(canvas);
+    ) -> Result<(), RenderError> {
+        self.rectangle.render(canvas)
     }
     fn update(&mut self, input: &Input) -> bool {
         if input.is_pressed(VirtualKeyCode::Space)
@@ -97,7 +160,8 @@ impl Widget for RandomRectangle {
                 fastrand::u8(0..255),
                 fastrand::u8(0..255),
                 255,
-            );
+            )
+            .into();
             true
         } else {
             false
@@ -0,0 +1,119 @@
+use crate::{
+    input::Input,
+    math::{ScaleFactor, Size2D},
+    rendering::{Canvas, DynRenderer, RenderError},
+    theme::Theme,
+};
+
+use super::{BoxConstraints, Widget};
+
+/// A `Widget` that can be used as a trait object, so widgets of different types can be stored
+/// together, e.g. as the children of a `Row<BoxedWidget>`, and swapped at runtime.
+///
+/// Every `Widget` is a `DynWidget`. Rendering through one goes through a `DynRenderer`, so prefer
+/// concrete widget types where the extra indirection matters.
+pub trait DynWidget: Widget {
+    /// Draws the widget into `canvas`, like `Widget::render`.
+    fn render_dyn(&mut self, canvas: Canvas<'_, DynRenderer<'_>>) -> Result<(), RenderError>;
+}
+impl<W: Widget> DynWidget for W {
+    fn render_dyn(&mut self, canvas: Canvas<'_, DynRenderer<'_>>) -> Result<(), RenderError> {
+        self.render(canvas)
+    }
+}
+
+/// A widget of any type. See `Widget::boxed`.
+pub type BoxedWidget = Box<dyn DynWidget>;
+
+impl Widget for BoxedWidget {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        (**self).layout(constraints)
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        (**self).min_intrinsic_width(height)
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        (**self).max_intrinsic_width(height)
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        (**self).min_intrinsic_height(width)
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        (**self).max_intrinsic_height(width)
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        mut canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        canvas.erased(|canvas| (**self).render_dyn(canvas))
+    }
+    fn update(&mut self, input: &Input) -> bool {
+        (**self).update(input)
+    }
+    fn focus(&mut self) -> bool {
+        (**self).focus()
+    }
+    fn scale_factor_changed(&mut self, scale_factor: ScaleFactor) {
+        (**self).scale_factor_changed(scale_factor)
+    }
+    fn theme_changed(&mut self, theme: &Theme) {
+        (**self).theme_changed(theme)
+    }
+    fn boxed(self) -> BoxedWidget {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        colors::Color,
+        math::{Box2D, Point2D},
+        rendering::RecordingRenderer,
+        widgets::{Column, MainAxisSize, Rectangle, Row},
+    };
+
+    fn rect(color: Color, width: f32, height: f32) -> Rectangle {
+        Rectangle {
+            color: color.into(),
+            max_size: Size2D::new(width, height),
+        }
+    }
+
+    #[test]
+    fn containers_hold_mixed_widgets() {
+        let mut row: Row<BoxedWidget> = Row::new()
+            .with_child(rect(Color::RED, 10.0, 10.0).boxed())
+            .with_child(
+                Column::new()
+                    .with_child(rect(Color::GREEN, 10.0, 5.0))
+                    .with_child(rect(Color::BLUE, 10.0, 5.0))
+                    .with_main_size(MainAxisSize::Min)
+                    .boxed(),
+            )
+            .with_main_size(MainAxisSize::Min);
+        assert_eq!(row.max_intrinsic_width(f32::INFINITY), 20.0);
+
+        let bounds = Box2D::new(Point2D::new(0.0, 0.0), Point2D::new(100.0, 100.0));
+        let mut renderer = RecordingRenderer::new();
+        renderer.render_widget(&mut row, bounds).unwrap();
+        renderer.assert_drawn(
+            Color::RED,
+            Box2D::new(Point2D::zero(), Point2D::new(10.0, 10.0)),
+        );
+        renderer.assert_drawn(
+            Color::BLUE,
+            Box2D::new(Point2D::new(10.0, 5.0), Point2D::new(20.0, 10.0)),
+        );
+
+        // Children can be swapped for widgets of another type.
+        row.children[1].widget = rect(Color::YELLOW, 30.0, 30.0).boxed();
+        let mut renderer = RecordingRenderer::new();
+        renderer.render_widget(&mut row, bounds).unwrap();
+        renderer.assert_drawn(
+            Color::YELLOW,
+            Box2D::new(Point2D::new(10.0, 0.0), Point2D::new(40.0, 30.0)),
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::math::Size2D;
+
+/// The sizes a parent allows a child to take, from `min` to `max` inclusive.
+///
+/// Parents pass constraints down in `Widget::layout`, children pick a size within them, and
+/// parents then decide where each child goes. A `max` of `f32::INFINITY` leaves that dimension
+/// unbounded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoxConstraints {
+    pub min: Size2D,
+    pub max: Size2D,
+}
+impl Default for BoxConstraints {
+    fn default() -> Self {
+        Self::UNBOUNDED
+    }
+}
+impl BoxConstraints {
+    /// Allows any size.
+    pub const UNBOUNDED: BoxConstraints = BoxConstraints {
+        min: Size2D::new(0.0, 0.0),
+        max: Size2D::new(f32::INFINITY, f32::INFINITY),
+    };
+
+    pub fn new(min: Size2D, max: Size2D) -> Self {
+        Self { min, max }
+    }
+    /// Allows exactly `size`.
+    pub fn tight(size: Size2D) -> Self {
+        Self::new(size, size)
+    }
+    /// Allows anything from nothing up to `max`.
+    pub fn loose(max: Size2D) -> Self {
+        Self::new(Size2D::zero(), max)
+    }
+    /// The same maximum, with no minimum.
+    pub fn loosen(&self) -> Self {
+        Self::loose(self.max)
+    }
+    /// Constraints for a child with `width` and `height` fixed where given, kept within these
+    /// constraints.
+    pub fn tighten(&self, width: Option<f32>, height: Option<f32>) -> Self {
+        let tight = |min: f32, max: f32, size: Option<f32>| {
+            size.map_or((min, max), |size| {
+                let size = size.clamp(min, max);
+                (size, size)
+            })
+        };
+        let (min_width, max_width) = tight(self.min.width, self.max.width, width);
+        let (min_height, max_height) = tight(self.min.height, self.max.height, height);
+        Self::new(
+            Size2D::new(min_width, min_height),
+            Size2D::new(max_width, max_height),
+        )
+    }
+    /// These constraints with `by` taken off both ends, e.g. to make room for padding.
+    pub fn deflate(&self, by: Size2D) -> Self {
+        let max = (self.max - by).max(Size2D::zero());
+        Self::new((self.min - by).max(Size2D::zero()).min(max), max)
+    }
+    /// The size within these constraints closest to `size`.
+    pub fn constrain(&self, size: Size2D) -> Size2D {
+        size.max(self.min).min(self.max)
+    }
+    /// These constraints, narrowed to fit within `other` as much as possible.
+    pub fn enforce(&self, other: BoxConstraints) -> Self {
+        Self::new(other.constrain(self.min), other.constrain(self.max))
+    }
+    /// The largest allowed size, using the minimum for unbounded dimensions.
+    pub fn biggest(&self) -> Size2D {
+        Size2D::new(
+            if self.max.width.is_finite() {
+                self.max.width
+            } else {
+                self.min.width
+            },
+            if self.max.height.is_finite() {
+                self.max.height
+            } else {
+                self.min.height
+            },
+        )
+    }
+    /// The smallest allowed size.
+    pub fn smallest(&self) -> Size2D {
+        self.min
+    }
+    /// Whether exactly one size is allowed.
+    pub fn is_tight(&self) -> bool {
+        self.min == self.max
+    }
+    pub fn has_bounded_width(&self) -> bool {
+        self.max.width.is_finite()
+    }
+    pub fn has_bounded_height(&self) -> bool {
+        self.max.height.is_finite()
+    }
+    /// Whether `size` is allowed.
+    pub fn contains(&self, size: Size2D) -> bool {
+        self.constrain(size) == size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constrains_sizes() {
+        let constraints = BoxConstraints::new(Size2D::new(10.0, 10.0), Size2D::new(100.0, 50.0));
+        assert_eq!(
+            constraints.constrain(Size2D::new(5.0, 80.0)),
+            Size2D::new(10.0, 50.0)
+        );
+        assert!(constraints.contains(Size2D::new(20.0, 20.0)));
+        assert!(!constraints.is_tight());
+        assert!(BoxConstraints::tight(Size2D::new(3.0, 4.0)).is_tight());
+        assert_eq!(
+            constraints.enforce(BoxConstraints::loose(Size2D::new(40.0, 5.0))),
+            BoxConstraints::new(Size2D::new(10.0, 5.0), Size2D::new(40.0, 5.0))
+        );
+
+        assert_eq!(
+            constraints.tighten(Some(500.0), None),
+            BoxConstraints::new(Size2D::new(100.0, 10.0), Size2D::new(100.0, 50.0))
+        );
+        assert_eq!(
+            constraints.deflate(Size2D::new(20.0, 20.0)),
+            BoxConstraints::new(Size2D::new(0.0, 0.0), Size2D::new(80.0, 30.0))
+        );
+
+        let unbounded = BoxConstraints::UNBOUNDED;
+        assert!(!unbounded.has_bounded_width());
+        assert_eq!(unbounded.biggest(), Size2D::zero());
+        assert_eq!(
+            unbounded.constrain(Size2D::new(1e6, 2.0)),
+            Size2D::new(1e6, 2.0)
+        );
+    }
+}
@@ -0,0 +1,630 @@
+use crate::{
+    input::Input,
+    math::{Box2D, Point2D, ScaleFactor, Size2D},
+    rendering::{Canvas, RenderError},
+    theme::Theme,
+};
+
+use super::{BoxConstraints, Expanded, Widget};
+
+/// The direction a `Row` or `Column` lays its children out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+impl Axis {
+    /// The extent of `size` along this axis.
+    pub fn main(self, size: Size2D) -> f32 {
+        match self {
+            Axis::Horizontal => size.width,
+            Axis::Vertical => size.height,
+        }
+    }
+    /// The extent of `size` across this axis.
+    pub fn cross(self, size: Size2D) -> f32 {
+        match self {
+            Axis::Horizontal => size.height,
+            Axis::Vertical => size.width,
+        }
+    }
+    /// A size from its extents along and across this axis.
+    pub fn size(self, main: f32, cross: f32) -> Size2D {
+        match self {
+            Axis::Horizontal => Size2D::new(main, cross),
+            Axis::Vertical => Size2D::new(cross, main),
+        }
+    }
+    fn point(self, main: f32, cross: f32) -> Point2D {
+        let size = self.size(main, cross);
+        Point2D::new(size.width, size.height)
+    }
+}
+
+/// Where children go along a container's main axis when they don't fill it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MainAxisAlignment {
+    #[default]
+    Start,
+    End,
+    Center,
+    /// Free space goes between children, none before the first or after the last.
+    SpaceBetween,
+    /// Free space goes around each child, so the ends get half as much as the gaps.
+    SpaceAround,
+    /// Free space is split evenly between the gaps and the ends.
+    SpaceEvenly,
+}
+
+/// Where children go across a container's main axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrossAxisAlignment {
+    #[default]
+    Start,
+    End,
+    Center,
+    /// Children are resized to fill their line.
+    Stretch,
+}
+
+/// How much of the main axis a container takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MainAxisSize {
+    /// All the space it's given.
+    #[default]
+    Max,
+    /// Only as much as its children need.
+    Min,
+}
+
+/// A child of a `Row` or `Column`, with how it flexes.
+#[derive(Debug, Clone)]
+pub struct FlexChild<W> {
+    pub widget: W,
+    /// This child's share of any free space on its line. `0.0` keeps its own size.
+    pub grow: f32,
+    /// How much this child gives up, weighted by its size, when its line overflows. `0.0` keeps
+    /// its own size.
+    pub shrink: f32,
+    /// The size along the main axis this child starts from before growing or shrinking. `None`
+    /// starts from its own size.
+    pub basis: Option<f32>,
+}
+impl<W> FlexChild<W> {
+    /// A child that keeps its own size unless its line overflows.
+    pub fn new(widget: W) -> Self {
+        Self {
+            widget,
+            grow: 0.0,
+            shrink: 1.0,
+            basis: None,
+        }
+    }
+    /// A child that takes a `grow` share of the free space on its line.
+    pub fn flexible(widget: W, grow: f32) -> Self {
+        Self {
+            grow,
+            ..Self::new(widget)
+        }
+    }
+}
+
+impl<W: Default> Default for FlexChild<W> {
+    fn default() -> Self {
+        Self::new(W::default())
+    }
+}
+
+/// The settings shared by `Row` and `Column`.
+struct Flex {
+    axis: Axis,
+    main_alignment: MainAxisAlignment,
+    cross_alignment: CrossAxisAlignment,
+    main_size: MainAxisSize,
+    spacing: f32,
+    wrap: bool,
+    run_spacing: f32,
+}
+impl Flex {
+    /// Lays `children` out, returning the container's size and the bounds of each child relative
+    /// to its origin.
+    ///
+    /// Children are measured by their max intrinsic sizes, then laid out once with a tight main
+    /// axis, after growing or shrinking. Laying them out only once keeps nested containers linear.
+    fn layout<W: Widget>(
+        &self,
+        children: &mut [FlexChild<W>],
+        constraints: BoxConstraints,
+    ) -> (Size2D, Vec<Box2D>) {
+        let axis = self.axis;
+        let max_main = axis.main(constraints.max);
+        let max_cross = axis.cross(constraints.max);
+        let sizes = children
+            .iter()
+            .map(|child| {
+                let widget = &child.widget;
+                let (main, cross) = match axis {
+                    Axis::Horizontal => {
+                        let width = widget.max_intrinsic_width(max_cross);
+                        (width, widget.max_intrinsic_height(width))
+                    }
+                    Axis::Vertical => {
+                        let height = widget.max_intrinsic_height(max_cross);
+                        (height, widget.max_intrinsic_width(height))
+                    }
+                };
+                axis.size(main, cross.min(max_cross))
+            })
+            .collect::<Vec<_>>();
+
+        // Break the children into lines, at most one if not wrapping.
+        let mut lines = Vec::with_capacity(1);
+        lines.push(0..0);
+        let mut used = 0.0;
+        for (i, size) in sizes.iter().enumerate() {
+            let line = lines.last_mut().unwrap();
+            let main = axis.main(*size);
+            if self.wrap && line.start < line.end && used + self.spacing + main > max_main {
+                lines.push(i..i + 1);
+                used = main;
+            } else {
+                used += if line.start == line.end {
+                    0.0
+                } else {
+                    self.spacing
+                } + main;
+                line.end = i + 1;
+            }
+        }
+
+        let mut mains = sizes
+            .iter()
+            .zip(children.iter())
+            .map(|(size, child)| child.basis.unwrap_or_else(|| axis.main(*size)))
+            .collect::<Vec<_>>();
+        let mut line_crosses = Vec::with_capacity(lines.len());
+        let mut content_main: f32 = 0.0;
+        for line in &lines {
+            let gaps = line.len().saturating_sub(1) as f32 * self.spacing;
+            let free = if max_main.is_finite() {
+                max_main - mains[line.clone()].iter().sum::<f32>() - gaps
+            } else {
+                0.0
+            };
+            let grow = line.clone().map(|i| children[i].grow).sum::<f32>();
+            let shrink = line
+                .clone()
+                .map(|i| children[i].shrink * mains[i])
+                .sum::<f32>();
+            for i in line.clone() {
+                if free > 0.0 && grow > 0.0 {
+                    mains[i] += free * children[i].grow / grow;
+                } else if free < 0.0 && shrink > 0.0 {
+                    mains[i] = (mains[i] + free * children[i].shrink * mains[i] / shrink).max(0.0);
+                }
+            }
+            content_main = content_main.max(mains[line.clone()].iter().sum::<f32>() + gaps);
+            line_crosses.push(
+                line.clone()
+                    .map(|i| axis.cross(sizes[i]))
+                    .fold(0.0, f32::max),
+            );
+        }
+        let main = match self.main_size {
+            MainAxisSize::Max if max_main.is_finite() => max_main,
+            _ => content_main,
+        };
+        let cross = if lines.len() == 1
+            && self.cross_alignment == CrossAxisAlignment::Stretch
+            && max_cross.is_finite()
+        {
+            max_cross
+        } else {
+            line_crosses.iter().sum::<f32>()
+                + line_crosses.len().saturating_sub(1) as f32 * self.run_spacing
+        };
+        let size = constraints.constrain(axis.size(main, cross));
+        let (container_main, container_cross) = (axis.main(size), axis.cross(size));
+        // A single line fills the container, so children align across all of it.
+        if lines.len() == 1 {
+            line_crosses[0] = container_cross;
+        }
+
+        let mut bounds = vec![Box2D::zero(); children.len()];
+        let mut line_start = 0.0;
+        for (line, line_cross) in lines.into_iter().zip(line_crosses) {
+            let count = line.len() as f32;
+            let gaps = (count - 1.0).max(0.0) * self.spacing;
+            let free = (container_main - mains[line.clone()].iter().sum::<f32>() - gaps).max(0.0);
+            let (mut main, between) = match self.main_alignment {
+                MainAxisAlignment::Start => (0.0, 0.0),
+                MainAxisAlignment::End => (free, 0.0),
+                MainAxisAlignment::Center => (free / 2.0, 0.0),
+                MainAxisAlignment::SpaceBetween if count > 1.0 => (0.0, free / (count - 1.0)),
+                MainAxisAlignment::SpaceBetween => (0.0, 0.0),
+                MainAxisAlignment::SpaceAround => (free / count / 2.0, free / count),
+                MainAxisAlignment::SpaceEvenly => (free / (count + 1.0), free / (count + 1.0)),
+            };
+            let stretch = self.cross_alignment == CrossAxisAlignment::Stretch;
+            let min_cross = if stretch { line_cross } else { 0.0 };
+            for i in line {
+                let size = children[i].widget.layout(BoxConstraints::new(
+                    axis.size(mains[i], min_cross),
+                    axis.size(mains[i], line_cross),
+                ));
+                let cross = axis.cross(size);
+                let offset = match self.cross_alignment {
+                    CrossAxisAlignment::Start | CrossAxisAlignment::Stretch => 0.0,
+                    CrossAxisAlignment::End => line_cross - cross,
+                    CrossAxisAlignment::Center => (line_cross - cross) / 2.0,
+                };
+                let origin = axis.point(main, line_start + offset);
+                bounds[i] = Box2D::new(origin, origin + size.to_vector());
+                main += axis.main(size) + self.spacing + between;
+            }
+            line_start += line_cross + self.run_spacing;
+        }
+        (size, bounds)
+    }
+    /// The container's intrinsic extent along `query`, given `extent` along the other axis.
+    fn intrinsic<W: Widget>(
+        &self,
+        children: &[FlexChild<W>],
+        query: Axis,
+        max: bool,
+        extent: f32,
+    ) -> f32 {
+        let child_extents = children.iter().map(|child| {
+            let widget = &child.widget;
+            match (query, max) {
+                (Axis::Horizontal, false) => widget.min_intrinsic_width(extent),
+                (Axis::Horizontal, true) => widget.max_intrinsic_width(extent),
+                (Axis::Vertical, false) => widget.min_intrinsic_height(extent),
+                (Axis::Vertical, true) => widget.max_intrinsic_height(extent),
+            }
+        });
+        if query != self.axis || (self.wrap && !max) {
+            // Across the axis, or the narrowest a wrapping container can get: the largest child.
+            child_extents.fold(0.0, f32::max)
+        } else {
+            child_extents.sum::<f32>() + children.len().saturating_sub(1) as f32 * self.spacing
+        }
+    }
+}
+
+macro_rules! flex_container {
+    ($(#[$attr:meta])* $name:ident, $axis:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $name<W> {
+            pub children: Vec<FlexChild<W>>,
+            pub main_alignment: MainAxisAlignment,
+            pub cross_alignment: CrossAxisAlignment,
+            pub main_size: MainAxisSize,
+            /// The gap between neighbouring children on a line.
+            pub spacing: f32,
+            /// Whether children that don't fit start a new line, instead of shrinking.
+            pub wrap: bool,
+            /// The gap between lines when wrapping.
+            pub run_spacing: f32,
+            child_bounds: Vec<Box2D>,
+        }
+        impl<W> Default for $name<W> {
+            fn default() -> Self {
+                Self {
+                    children: Vec::new(),
+                    main_alignment: MainAxisAlignment::default(),
+                    cross_alignment: CrossAxisAlignment::default(),
+                    main_size: MainAxisSize::default(),
+                    spacing: 0.0,
+                    wrap: false,
+                    run_spacing: 0.0,
+                    child_bounds: Vec::new(),
+                }
+            }
+        }
+        impl<W> $name<W> {
+            pub fn new() -> Self {
+                Self::default()
+            }
+            pub fn with_child(mut self, widget: W) -> Self {
+                self.children.push(FlexChild::new(widget));
+                self
+            }
+            /// Adds a child that takes a `grow` share of the free space on its line.
+            pub fn with_flexible_child(mut self, widget: W, grow: f32) -> Self {
+                self.children.push(FlexChild::flexible(widget, grow));
+                self
+            }
+            /// Adds a child that starts from nothing and takes its share of the free space.
+            pub fn with_expanded_child(mut self, expanded: Expanded<W>) -> Self {
+                self.children.push(expanded.into());
+                self
+            }
+            pub fn with_main_alignment(mut self, alignment: MainAxisAlignment) -> Self {
+                self.main_alignment = alignment;
+                self
+            }
+            pub fn with_cross_alignment(mut self, alignment: CrossAxisAlignment) -> Self {
+                self.cross_alignment = alignment;
+                self
+            }
+            pub fn with_main_size(mut self, main_size: MainAxisSize) -> Self {
+                self.main_size = main_size;
+                self
+            }
+            pub fn with_spacing(mut self, spacing: f32) -> Self {
+                self.spacing = spacing;
+                self
+            }
+            /// Wraps children onto new lines `run_spacing` apart when they don't fit.
+            pub fn with_wrap(mut self, run_spacing: f32) -> Self {
+                self.wrap = true;
+                self.run_spacing = run_spacing;
+                self
+            }
+            fn flex(&self) -> Flex {
+                Flex {
+                    axis: $axis,
+                    main_alignment: self.main_alignment,
+                    cross_alignment: self.cross_alignment,
+                    main_size: self.main_size,
+                    spacing: self.spacing,
+                    wrap: self.wrap,
+                    run_spacing: self.run_spacing,
+                }
+            }
+            /// The bounds of each child relative to the container, as of the last layout.
+            pub fn child_bounds(&self) -> &[Box2D] {
+                &self.child_bounds
+            }
+        }
+        impl<W: Widget> Widget for $name<W> {
+            fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+                let (size, bounds) = self.flex().layout(&mut self.children, constraints);
+                self.child_bounds = bounds;
+                size
+            }
+            fn min_intrinsic_width(&self, height: f32) -> f32 {
+                self.flex().intrinsic(&self.children, Axis::Horizontal, false, height)
+            }
+            fn max_intrinsic_width(&self, height: f32) -> f32 {
+                self.flex().intrinsic(&self.children, Axis::Horizontal, true, height)
+            }
+            fn min_intrinsic_height(&self, width: f32) -> f32 {
+                self.flex().intrinsic(&self.children, Axis::Vertical, false, width)
+            }
+            fn max_intrinsic_height(&self, width: f32) -> f32 {
+                self.flex().intrinsic(&self.children, Axis::Vertical, true, width)
+            }
+            fn render<Renderer: crate::rendering::Renderer>(
+                &mut self,
+                mut canvas: Canvas<'_, Renderer>,
+            ) -> Result<(), RenderError> {
+                let origin = canvas.bounds().min.to_vector();
+                for (child, bounds) in self.children.iter_mut().zip(&self.child_bounds) {
+                    canvas.render(&mut child.widget, bounds.translate(origin))?;
+                }
+                Ok(())
+            }
+            fn update(&mut self, input: &Input) -> bool {
+                self.children
+                    .iter_mut()
+                    .fold(false, |changed, child| child.widget.update(input) || changed)
+            }
+            /// Focuses the first child that accepts focus.
+            fn focus(&mut self) -> bool {
+                self.children.iter_mut().any(|child| child.widget.focus())
+            }
+            fn scale_factor_changed(&mut self, scale_factor: ScaleFactor) {
+                for child in &mut self.children {
+                    child.widget.scale_factor_changed(scale_factor);
+                }
+            }
+            fn theme_changed(&mut self, theme: &Theme) {
+                for child in &mut self.children {
+                    child.widget.theme_changed(theme);
+                }
+            }
+        }
+    };
+}
+
+flex_container!(
+    /// Lays its children out left to right.
+    Row,
+    Axis::Horizontal
+);
+flex_container!(
+    /// Lays its children out top to bottom.
+    Column,
+    Axis::Vertical
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colors::Color, rendering::RecordingRenderer, widgets::Rectangle};
+
+    fn rect(width: f32, height: f32) -> Rectangle {
+        Rectangle {
+            color: Color::RED.into(),
+            max_size: Size2D::new(width, height),
+        }
+    }
+    fn area(x0: f32, y0: f32, x1: f32, y1: f32) -> Box2D {
+        Box2D::new(Point2D::new(x0, y0), Point2D::new(x1, y1))
+    }
+
+    #[test]
+    fn rows_space_and_align_children() {
+        let mut row = Row::new()
+            .with_child(rect(10.0, 10.0))
+            .with_child(rect(20.0, 30.0))
+            .with_spacing(5.0)
+            .with_cross_alignment(CrossAxisAlignment::Center);
+        let max = BoxConstraints::loose(Size2D::new(100.0, 50.0));
+        assert_eq!(row.layout(max), Size2D::new(100.0, 30.0));
+        assert_eq!(
+            row.child_bounds(),
+            [area(0.0, 10.0, 10.0, 20.0), area(15.0, 0.0, 35.0, 30.0)]
+        );
+        // A tight cross axis centers children across all of it.
+        row.layout(BoxConstraints::tight(Size2D::new(100.0, 50.0)));
+        assert_eq!(row.child_bounds()[0], area(0.0, 20.0, 10.0, 30.0));
+
+        let mut row = row
+            .with_main_alignment(MainAxisAlignment::SpaceBetween)
+            .with_main_size(MainAxisSize::Min);
+        assert_eq!(row.layout(max), Size2D::new(35.0, 30.0));
+        let mut row = row.with_main_size(MainAxisSize::Max);
+        row.layout(max);
+        assert_eq!(row.child_bounds()[1], area(80.0, 0.0, 100.0, 30.0));
+        let mut row = row.with_main_alignment(MainAxisAlignment::SpaceEvenly);
+        row.layout(max);
+        assert_eq!(row.child_bounds()[0].min.x, 65.0 / 3.0);
+        // Unbounded rows are as long as their children.
+        assert_eq!(
+            row.layout(BoxConstraints::UNBOUNDED),
+            Size2D::new(35.0, 30.0)
+        );
+    }
+
+    /// Takes whatever size it is given, preferring its own.
+    struct Stretchy(Size2D);
+    impl Widget for Stretchy {
+        fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+            constraints.constrain(self.0)
+        }
+        fn min_intrinsic_width(&self, _height: f32) -> f32 {
+            self.0.width
+        }
+        fn min_intrinsic_height(&self, _width: f32) -> f32 {
+            self.0.height
+        }
+        fn render<Renderer: crate::rendering::Renderer>(
+            &mut self,
+            _canvas: Canvas<'_, Renderer>,
+        ) -> Result<(), RenderError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn children_grow_and_shrink() {
+        let mut column = Column::new()
+            .with_flexible_child(Stretchy(Size2D::new(10.0, 10.0)), 1.0)
+            .with_flexible_child(Stretchy(Size2D::new(10.0, 10.0)), 3.0);
+        let max = BoxConstraints::loose(Size2D::new(50.0, 100.0));
+        column.layout(max);
+        assert_eq!(
+            column.child_bounds(),
+            [area(0.0, 0.0, 10.0, 30.0), area(0.0, 30.0, 10.0, 100.0)]
+        );
+        let mut column = column.with_cross_alignment(CrossAxisAlignment::Stretch);
+        assert_eq!(column.layout(max), Size2D::new(50.0, 100.0));
+        assert_eq!(column.child_bounds()[1], area(0.0, 30.0, 50.0, 100.0));
+
+        // Overflowing lines shrink children in proportion to their size.
+        let mut row = Row::new()
+            .with_child(Stretchy(Size2D::new(60.0, 10.0)))
+            .with_child(Stretchy(Size2D::new(40.0, 10.0)));
+        row.layout(BoxConstraints::loose(Size2D::new(80.0, 10.0)));
+        assert_eq!(
+            row.child_bounds(),
+            [area(0.0, 0.0, 48.0, 10.0), area(48.0, 0.0, 80.0, 10.0)]
+        );
+        // Children that don't shrink overflow instead.
+        row.children[0].shrink = 0.0;
+        row.layout(BoxConstraints::loose(Size2D::new(80.0, 10.0)));
+        assert_eq!(
+            row.child_bounds(),
+            [area(0.0, 0.0, 60.0, 10.0), area(60.0, 0.0, 80.0, 10.0)]
+        );
+    }
+
+    /// Counts how many times it is laid out.
+    #[derive(Default)]
+    struct Counted(usize);
+    impl Widget for Counted {
+        fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+            self.0 += 1;
+            constraints.constrain(Size2D::new(10.0, 10.0))
+        }
+        fn render<Renderer: crate::rendering::Renderer>(
+            &mut self,
+            _canvas: Canvas<'_, Renderer>,
+        ) -> Result<(), RenderError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn children_are_laid_out_once() {
+        fn column<W>(child: W) -> Column<W> {
+            Column::new()
+                .with_flexible_child(child, 1.0)
+                .with_cross_alignment(CrossAxisAlignment::Stretch)
+        }
+        let mut nested = column(column(column(Counted::default())));
+        nested.layout(BoxConstraints::loose(Size2D::new(100.0, 100.0)));
+        let innermost = &nested.children[0].widget.children[0].widget.children[0];
+        assert_eq!(innermost.widget.0, 1);
+
+        let child = FlexChild::<Counted>::default();
+        assert_eq!((child.grow, child.shrink), (0.0, 1.0));
+    }
+
+    #[test]
+    fn rows_wrap_onto_new_lines() {
+        let mut row = Row::new()
+            .with_child(rect(40.0, 10.0))
+            .with_child(rect(40.0, 20.0))
+            .with_child(rect(40.0, 10.0))
+            .with_spacing(10.0)
+            .with_wrap(5.0);
+        let max = BoxConstraints::loose(Size2D::new(100.0, 100.0));
+        assert_eq!(row.layout(max), Size2D::new(100.0, 35.0));
+        assert_eq!(
+            row.child_bounds(),
+            [
+                area(0.0, 0.0, 40.0, 10.0),
+                area(50.0, 0.0, 90.0, 20.0),
+                area(0.0, 25.0, 40.0, 35.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn intrinsic_sizes_add_up_along_the_axis() {
+        let row = Row::new()
+            .with_child(rect(10.0, 10.0))
+            .with_child(rect(20.0, 30.0))
+            .with_spacing(5.0);
+        assert_eq!(row.max_intrinsic_width(f32::INFINITY), 35.0);
+        assert_eq!(row.min_intrinsic_height(35.0), 30.0);
+        assert_eq!(row.with_wrap(0.0).min_intrinsic_width(f32::INFINITY), 20.0);
+        let column = Column::new()
+            .with_child(rect(10.0, 10.0))
+            .with_child(rect(20.0, 30.0));
+        assert_eq!(column.max_intrinsic_height(f32::INFINITY), 40.0);
+        assert_eq!(column.max_intrinsic_width(40.0), 20.0);
+    }
+
+    #[test]
+    fn renders_children_in_their_bounds() {
+        let mut column = Column::new()
+            .with_child(rect(10.0, 10.0))
+            .with_child(Rectangle {
+                color: Color::BLUE.into(),
+                max_size: Size2D::new(10.0, 10.0),
+            })
+            .with_main_alignment(MainAxisAlignment::End);
+        let mut renderer = RecordingRenderer::new();
+        renderer
+            .render_widget(&mut column, area(100.0, 100.0, 120.0, 150.0))
+            .unwrap();
+        renderer.assert_drawn(Color::RED, area(100.0, 130.0, 110.0, 140.0));
+        renderer.assert_drawn(Color::BLUE, area(100.0, 140.0, 110.0, 150.0));
+    }
+}
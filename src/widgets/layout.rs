@@ -0,0 +1,893 @@
+use crate::{
+    input::Input,
+    math::{Box2D, Point2D, ScaleFactor, Size2D, Vector2D},
+    rendering::{Canvas, RenderError},
+    theme::Theme,
+};
+
+use super::{BoxConstraints, FlexChild, Widget};
+
+/// Implements the `Widget` methods that just pass events on to the wrapped widget in `$child`.
+macro_rules! forward_to_child {
+    ($child:tt) => {
+        fn update(&mut self, input: &Input) -> bool {
+            self.$child.update(input)
+        }
+        fn focus(&mut self) -> bool {
+            self.$child.focus()
+        }
+        fn scale_factor_changed(&mut self, scale_factor: ScaleFactor) {
+            self.$child.scale_factor_changed(scale_factor)
+        }
+        fn theme_changed(&mut self, theme: &Theme) {
+            self.$child.theme_changed(theme)
+        }
+    };
+}
+
+/// Renders `child` at `bounds`, relative to the canvas.
+fn render_child<W: Widget, Renderer: crate::rendering::Renderer>(
+    canvas: &mut Canvas<'_, Renderer>,
+    child: &mut W,
+    bounds: Box2D,
+) -> Result<(), RenderError> {
+    let origin = canvas.bounds().min.to_vector();
+    canvas.render(child, bounds.translate(origin))
+}
+
+/// Space around each edge of a box, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeInsets {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+impl EdgeInsets {
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+    /// The same inset on every edge.
+    pub fn all(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+    /// `horizontal` on the left and right, and `vertical` on the top and bottom.
+    pub fn symmetric(horizontal: f32, vertical: f32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+    /// The total width and height of the insets.
+    pub fn size(&self) -> Size2D {
+        Size2D::new(self.left + self.right, self.top + self.bottom)
+    }
+}
+
+/// Puts space around its child.
+#[derive(Debug, Clone)]
+pub struct Padding<W> {
+    pub insets: EdgeInsets,
+    pub child: W,
+    child_bounds: Box2D,
+}
+impl<W> Padding<W> {
+    pub fn new(insets: EdgeInsets, child: W) -> Self {
+        Self {
+            insets,
+            child,
+            child_bounds: Box2D::zero(),
+        }
+    }
+}
+impl<W: Widget> Widget for Padding<W> {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        let insets = self.insets.size();
+        let size = self.child.layout(constraints.deflate(insets));
+        let origin = Point2D::new(self.insets.left, self.insets.top);
+        self.child_bounds = Box2D::new(origin, origin + size.to_vector());
+        constraints.constrain(size + insets)
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        let insets = self.insets.size();
+        let height = (height - insets.height).max(0.0);
+        self.child.min_intrinsic_width(height) + insets.width
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        let insets = self.insets.size();
+        let height = (height - insets.height).max(0.0);
+        self.child.max_intrinsic_width(height) + insets.width
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        let insets = self.insets.size();
+        let width = (width - insets.width).max(0.0);
+        self.child.min_intrinsic_height(width) + insets.height
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        let insets = self.insets.size();
+        let width = (width - insets.width).max(0.0);
+        self.child.max_intrinsic_height(width) + insets.height
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        mut canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        render_child(&mut canvas, &mut self.child, self.child_bounds)
+    }
+    forward_to_child!(child);
+}
+
+/// Where a child goes within a larger box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Alignment {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+impl Alignment {
+    /// Where the child's top-left corner goes, as a fraction of the free space on each axis.
+    pub fn factors(self) -> Vector2D {
+        let (x, y) = match self {
+            Alignment::TopLeft => (0.0, 0.0),
+            Alignment::Top => (0.5, 0.0),
+            Alignment::TopRight => (1.0, 0.0),
+            Alignment::Left => (0.0, 0.5),
+            Alignment::Center => (0.5, 0.5),
+            Alignment::Right => (1.0, 0.5),
+            Alignment::BottomLeft => (0.0, 1.0),
+            Alignment::Bottom => (0.5, 1.0),
+            Alignment::BottomRight => (1.0, 1.0),
+        };
+        Vector2D::new(x, y)
+    }
+    /// The top-left corner of a `child` sized box aligned within a `container` sized one.
+    pub fn place(self, child: Size2D, container: Size2D) -> Point2D {
+        let free = container - child;
+        let factors = self.factors();
+        Point2D::new(free.width * factors.x, free.height * factors.y)
+    }
+}
+
+/// Takes all the space it's given, and places its child within it.
+///
+/// In unbounded dimensions it is as big as its child.
+#[derive(Debug, Clone)]
+pub struct Align<W> {
+    pub alignment: Alignment,
+    pub child: W,
+    child_bounds: Box2D,
+}
+impl<W> Align<W> {
+    pub fn new(alignment: Alignment, child: W) -> Self {
+        Self {
+            alignment,
+            child,
+            child_bounds: Box2D::zero(),
+        }
+    }
+}
+impl<W: Widget> Widget for Align<W> {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        let child = self.child.layout(constraints.loosen());
+        let size = constraints.constrain(Size2D::new(
+            if constraints.has_bounded_width() {
+                constraints.max.width
+            } else {
+                child.width
+            },
+            if constraints.has_bounded_height() {
+                constraints.max.height
+            } else {
+                child.height
+            },
+        ));
+        let origin = self.alignment.place(child, size);
+        self.child_bounds = Box2D::new(origin, origin + child.to_vector());
+        size
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        self.child.min_intrinsic_width(height)
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        self.child.max_intrinsic_width(height)
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        self.child.min_intrinsic_height(width)
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        self.child.max_intrinsic_height(width)
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        mut canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        render_child(&mut canvas, &mut self.child, self.child_bounds)
+    }
+    forward_to_child!(child);
+}
+
+/// An `Align` that centers its child.
+#[derive(Debug, Clone)]
+pub struct Center<W>(pub Align<W>);
+impl<W> Center<W> {
+    pub fn new(child: W) -> Self {
+        Self(Align::new(Alignment::Center, child))
+    }
+}
+impl<W: Widget> Widget for Center<W> {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        self.0.layout(constraints)
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        self.0.min_intrinsic_width(height)
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        self.0.max_intrinsic_width(height)
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        self.0.min_intrinsic_height(width)
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        self.0.max_intrinsic_height(width)
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        self.0.render(canvas)
+    }
+    forward_to_child!(0);
+}
+
+/// Gives its child a fixed width, height, or both, as far as its own constraints allow.
+#[derive(Debug, Clone)]
+pub struct SizedBox<W> {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    pub child: W,
+}
+impl<W> SizedBox<W> {
+    pub fn new(width: Option<f32>, height: Option<f32>, child: W) -> Self {
+        Self {
+            width,
+            height,
+            child,
+        }
+    }
+    /// A box of exactly `size`.
+    pub fn from_size(size: Size2D, child: W) -> Self {
+        Self::new(Some(size.width), Some(size.height), child)
+    }
+}
+impl<W: Widget> Widget for SizedBox<W> {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        let size = self
+            .child
+            .layout(constraints.tighten(self.width, self.height));
+        constraints.constrain(size)
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        self.width.unwrap_or_else(|| {
+            self.child
+                .min_intrinsic_width(self.height.unwrap_or(height))
+        })
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        self.width.unwrap_or_else(|| {
+            self.child
+                .max_intrinsic_width(self.height.unwrap_or(height))
+        })
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        self.height
+            .unwrap_or_else(|| self.child.min_intrinsic_height(self.width.unwrap_or(width)))
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        self.height
+            .unwrap_or_else(|| self.child.max_intrinsic_height(self.width.unwrap_or(width)))
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        self.child.render(canvas)
+    }
+    forward_to_child!(child);
+}
+
+/// Adds its own constraints to the ones its child is given.
+#[derive(Debug, Clone)]
+pub struct ConstrainedBox<W> {
+    pub constraints: BoxConstraints,
+    pub child: W,
+}
+impl<W> ConstrainedBox<W> {
+    pub fn new(constraints: BoxConstraints, child: W) -> Self {
+        Self { constraints, child }
+    }
+}
+impl<W: Widget> Widget for ConstrainedBox<W> {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        self.child.layout(self.constraints.enforce(constraints))
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        let width = self.child.min_intrinsic_width(height);
+        width.clamp(self.constraints.min.width, self.constraints.max.width)
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        let width = self.child.max_intrinsic_width(height);
+        width.clamp(self.constraints.min.width, self.constraints.max.width)
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        let height = self.child.min_intrinsic_height(width);
+        height.clamp(self.constraints.min.height, self.constraints.max.height)
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        let height = self.child.max_intrinsic_height(width);
+        height.clamp(self.constraints.min.height, self.constraints.max.height)
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        self.child.render(canvas)
+    }
+    forward_to_child!(child);
+}
+
+fn is_valid_ratio(ratio: f32) -> bool {
+    ratio > 0.0 && ratio.is_finite()
+}
+
+/// Sizes its child to a fixed ratio of width to height, as large as its constraints allow.
+#[derive(Debug, Clone)]
+pub struct AspectRatio<W> {
+    /// Width divided by height. Always positive and finite, so sizes can be divided by it.
+    ratio: f32,
+    pub child: W,
+}
+impl<W> AspectRatio<W> {
+    /// `None` if `ratio` isn't positive and finite.
+    pub fn new(ratio: f32, child: W) -> Option<Self> {
+        is_valid_ratio(ratio).then_some(Self { ratio, child })
+    }
+    /// Width divided by height.
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+    /// Changes the ratio, returning `false` and keeping the old one if `ratio` isn't positive and
+    /// finite.
+    #[must_use]
+    pub fn set_ratio(&mut self, ratio: f32) -> bool {
+        let valid = is_valid_ratio(ratio);
+        if valid {
+            self.ratio = ratio;
+        }
+        valid
+    }
+    /// The largest size with the right ratio within `constraints`, or as close as they allow.
+    fn size(&self, constraints: BoxConstraints) -> Size2D {
+        if constraints.is_tight() {
+            return constraints.min;
+        }
+        let mut width = constraints.max.width;
+        let mut height = width / self.ratio;
+        if !width.is_finite() || height > constraints.max.height {
+            height = constraints.max.height;
+            width = height * self.ratio;
+        }
+        if !height.is_finite() {
+            // Unbounded both ways, so fall back to the smallest allowed size.
+            width = constraints
+                .min
+                .width
+                .max(constraints.min.height * self.ratio);
+            height = width / self.ratio;
+        }
+        if width < constraints.min.width {
+            width = constraints.min.width;
+            height = width / self.ratio;
+        }
+        if height < constraints.min.height {
+            height = constraints.min.height;
+            width = height * self.ratio;
+        }
+        constraints.constrain(Size2D::new(width, height))
+    }
+}
+impl<W: Widget> Widget for AspectRatio<W> {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        let size = self.size(constraints);
+        self.child.layout(BoxConstraints::tight(size));
+        size
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        if height.is_finite() {
+            height * self.ratio
+        } else {
+            self.child.min_intrinsic_width(height)
+        }
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        if height.is_finite() {
+            height * self.ratio
+        } else {
+            self.child.max_intrinsic_width(height)
+        }
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        if width.is_finite() {
+            width / self.ratio
+        } else {
+            self.child.min_intrinsic_height(width)
+        }
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        if width.is_finite() {
+            width / self.ratio
+        } else {
+            self.child.max_intrinsic_height(width)
+        }
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        self.child.render(canvas)
+    }
+    forward_to_child!(child);
+}
+
+/// A `Row` or `Column` child that takes a `flex` share of the free space, in place of its own size
+/// along the container's axis. Added with `with_expanded_child`.
+#[derive(Debug, Clone)]
+pub struct Expanded<W> {
+    pub flex: f32,
+    pub child: W,
+}
+impl<W> Expanded<W> {
+    /// Takes an equal share of the free space with other `Expanded` children.
+    pub fn new(child: W) -> Self {
+        Self::with_flex(1.0, child)
+    }
+    pub fn with_flex(flex: f32, child: W) -> Self {
+        Self { flex, child }
+    }
+}
+impl<W> From<Expanded<W>> for FlexChild<W> {
+    fn from(expanded: Expanded<W>) -> Self {
+        Self {
+            basis: Some(0.0),
+            ..Self::flexible(expanded.child, expanded.flex)
+        }
+    }
+}
+
+/// Where a `Stack` child goes, by its distance from the stack's edges.
+///
+/// Giving both `left` and `right` fixes the child's width, as does giving `width`; likewise for
+/// `top`, `bottom` and `height`. Unset edges fall back to the stack's alignment.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Positioned {
+    pub left: Option<f32>,
+    pub top: Option<f32>,
+    pub right: Option<f32>,
+    pub bottom: Option<f32>,
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+}
+impl Positioned {
+    /// Covers the whole stack, `inset` from each edge.
+    pub fn fill(inset: EdgeInsets) -> Self {
+        Self {
+            left: Some(inset.left),
+            top: Some(inset.top),
+            right: Some(inset.right),
+            bottom: Some(inset.bottom),
+            ..Self::default()
+        }
+    }
+    /// The extent the child is fixed to along one axis of a stack `extent` long, from both of its
+    /// edges or its `size`, before it is laid out. `None` lets the child pick.
+    fn fixed_extent(
+        start: Option<f32>,
+        end: Option<f32>,
+        size: Option<f32>,
+        extent: f32,
+    ) -> Option<f32> {
+        match (start, end) {
+            (Some(start), Some(end)) => Some((extent - start - end).max(0.0)),
+            _ => size,
+        }
+    }
+    fn offset(start: Option<f32>, end: Option<f32>, child: f32, extent: f32, factor: f32) -> f32 {
+        match (start, end) {
+            (Some(start), _) => start,
+            (None, Some(end)) => extent - end - child,
+            (None, None) => (extent - child) * factor,
+        }
+    }
+}
+
+/// A child of a `Stack`, with where it goes.
+#[derive(Debug, Clone, Default)]
+pub struct StackChild<W> {
+    pub widget: W,
+    /// `None` to place the child with the stack's alignment, and count it towards the stack's
+    /// size.
+    pub position: Option<Positioned>,
+}
+
+/// Draws its children over each other, in order.
+///
+/// The stack is as big as its largest unpositioned child, or as big as it can be if all of its
+/// children are positioned.
+#[derive(Debug, Clone)]
+pub struct Stack<W> {
+    pub children: Vec<StackChild<W>>,
+    /// Where unpositioned children go.
+    pub alignment: Alignment,
+    child_bounds: Vec<Box2D>,
+}
+impl<W> Default for Stack<W> {
+    fn default() -> Self {
+        Self {
+            children: Vec::new(),
+            alignment: Alignment::TopLeft,
+            child_bounds: Vec::new(),
+        }
+    }
+}
+impl<W> Stack<W> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+    /// Adds a child placed with the stack's alignment.
+    pub fn with_child(mut self, widget: W) -> Self {
+        self.children.push(StackChild {
+            widget,
+            position: None,
+        });
+        self
+    }
+    /// Adds a child placed at `position`.
+    pub fn with_positioned_child(mut self, position: Positioned, widget: W) -> Self {
+        self.children.push(StackChild {
+            widget,
+            position: Some(position),
+        });
+        self
+    }
+    /// The bounds of each child relative to the stack, as of the last layout.
+    pub fn child_bounds(&self) -> &[Box2D] {
+        &self.child_bounds
+    }
+}
+impl<W: Widget> Widget for Stack<W> {
+    fn layout(&mut self, constraints: BoxConstraints) -> Size2D {
+        let mut sizes = vec![None; self.children.len()];
+        let mut largest = None;
+        for (child, size) in self.children.iter_mut().zip(&mut sizes) {
+            if child.position.is_none() {
+                let child_size = child.widget.layout(constraints.loosen());
+                largest = Some(largest.unwrap_or(Size2D::zero()).max(child_size));
+                *size = Some(child_size);
+            }
+        }
+        let size = match largest {
+            Some(largest) => constraints.constrain(largest),
+            None => constraints.biggest(),
+        };
+        let factors = self.alignment.factors();
+        self.child_bounds = self
+            .children
+            .iter_mut()
+            .zip(sizes)
+            .map(|(child, child_size)| {
+                let (child_size, origin) = match child.position {
+                    None => {
+                        let child_size = child_size.unwrap_or_default();
+                        (child_size, self.alignment.place(child_size, size))
+                    }
+                    Some(position) => {
+                        let width = Positioned::fixed_extent(
+                            position.left,
+                            position.right,
+                            position.width,
+                            size.width,
+                        );
+                        let height = Positioned::fixed_extent(
+                            position.top,
+                            position.bottom,
+                            position.height,
+                            size.height,
+                        );
+                        let child_size = child
+                            .widget
+                            .layout(BoxConstraints::UNBOUNDED.tighten(width, height));
+                        let origin = Point2D::new(
+                            Positioned::offset(
+                                position.left,
+                                position.right,
+                                child_size.width,
+                                size.width,
+                                factors.x,
+                            ),
+                            Positioned::offset(
+                                position.top,
+                                position.bottom,
+                                child_size.height,
+                                size.height,
+                                factors.y,
+                            ),
+                        );
+                        (child_size, origin)
+                    }
+                };
+                Box2D::new(origin, origin + child_size.to_vector())
+            })
+            .collect();
+        size
+    }
+    fn min_intrinsic_width(&self, height: f32) -> f32 {
+        self.unpositioned()
+            .map(|child| child.min_intrinsic_width(height))
+            .fold(0.0, f32::max)
+    }
+    fn max_intrinsic_width(&self, height: f32) -> f32 {
+        self.unpositioned()
+            .map(|child| child.max_intrinsic_width(height))
+            .fold(0.0, f32::max)
+    }
+    fn min_intrinsic_height(&self, width: f32) -> f32 {
+        self.unpositioned()
+            .map(|child| child.min_intrinsic_height(width))
+            .fold(0.0, f32::max)
+    }
+    fn max_intrinsic_height(&self, width: f32) -> f32 {
+        self.unpositioned()
+            .map(|child| child.max_intrinsic_height(width))
+            .fold(0.0, f32::max)
+    }
+    fn render<Renderer: crate::rendering::Renderer>(
+        &mut self,
+        mut canvas: Canvas<'_, Renderer>,
+    ) -> Result<(), RenderError> {
+        for (child, bounds) in self.children.iter_mut().zip(&self.child_bounds) {
+            render_child(&mut canvas, &mut child.widget, *bounds)?;
+        }
+        Ok(())
+    }
+    fn update(&mut self, input: &Input) -> bool {
+        let mut changed = false;
+        for child in &mut self.children {
+            changed |= child.widget.update(input);
+        }
+        changed
+    }
+    /// Focuses the first child that accepts focus.
+    fn focus(&mut self) -> bool {
+        self.children.iter_mut().any(|child| child.widget.focus())
+    }
+    fn scale_factor_changed(&mut self, scale_factor: ScaleFactor) {
+        for child in &mut self.children {
+            child.widget.scale_factor_changed(scale_factor);
+        }
+    }
+    fn theme_changed(&mut self, theme: &Theme) {
+        for child in &mut self.children {
+            child.widget.theme_changed(theme);
+        }
+    }
+}
+impl<W> Stack<W> {
+    fn unpositioned(&self) -> impl Iterator<Item = &W> {
+        self.children
+            .iter()
+            .filter(|child| child.position.is_none())
+            .map(|child| &child.widget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        colors::Color,
+        rendering::RecordingRenderer,
+        widgets::{Rectangle, Row},
+    };
+
+    fn rect(width: f32, height: f32) -> Rectangle {
+        Rectangle {
+            color: Color::RED.into(),
+            max_size: Size2D::new(width, height),
+        }
+    }
+    fn area(x0: f32, y0: f32, x1: f32, y1: f32) -> Box2D {
+        Box2D::new(Point2D::new(x0, y0), Point2D::new(x1, y1))
+    }
+    fn loose(width: f32, height: f32) -> BoxConstraints {
+        BoxConstraints::loose(Size2D::new(width, height))
+    }
+
+    #[test]
+    fn padding_insets_its_child() {
+        let mut padding = Padding::new(EdgeInsets::new(1.0, 2.0, 3.0, 4.0), rect(10.0, 10.0));
+        assert_eq!(padding.layout(loose(100.0, 100.0)), Size2D::new(16.0, 14.0));
+        assert_eq!(padding.max_intrinsic_width(f32::INFINITY), 16.0);
+        let mut renderer = RecordingRenderer::new();
+        renderer
+            .render_widget(&mut padding, area(10.0, 10.0, 110.0, 110.0))
+            .unwrap();
+        renderer.assert_drawn(Color::RED, area(14.0, 11.0, 24.0, 21.0));
+        // Padding squeezes its child when space runs out.
+        assert_eq!(padding.layout(loose(12.0, 100.0)), Size2D::new(12.0, 14.0));
+        // The child never gets a negative extent, even when the insets don't fit.
+        let padding = Padding::new(
+            EdgeInsets::new(1.0, 2.0, 3.0, 4.0),
+            AspectRatio::new(2.0, rect(10.0, 10.0)).unwrap(),
+        );
+        assert_eq!(padding.min_intrinsic_width(2.0), 6.0);
+        assert_eq!(padding.max_intrinsic_height(4.0), 4.0);
+    }
+
+    #[test]
+    fn align_and_center_place_their_child() {
+        let mut align = Align::new(Alignment::BottomRight, rect(10.0, 10.0));
+        assert_eq!(align.layout(loose(100.0, 50.0)), Size2D::new(100.0, 50.0));
+        assert_eq!(align.child_bounds, area(90.0, 40.0, 100.0, 50.0));
+        // Unbounded dimensions shrink to the child.
+        let constraints = BoxConstraints::new(Size2D::zero(), Size2D::new(100.0, f32::INFINITY));
+        assert_eq!(align.layout(constraints), Size2D::new(100.0, 10.0));
+
+        let mut center = Center::new(rect(10.0, 20.0));
+        let mut renderer = RecordingRenderer::new();
+        renderer
+            .render_widget(&mut center, area(0.0, 0.0, 100.0, 100.0))
+            .unwrap();
+        renderer.assert_drawn(Color::RED, area(45.0, 40.0, 55.0, 60.0));
+    }
+
+    #[test]
+    fn sized_and_constrained_boxes_limit_their_child() {
+        let mut sized = SizedBox::new(Some(30.0), None, rect(10.0, 10.0));
+        assert_eq!(sized.layout(loose(100.0, 100.0)), Size2D::new(30.0, 10.0));
+        assert_eq!(sized.layout(loose(20.0, 100.0)), Size2D::new(20.0, 10.0));
+        assert_eq!(sized.max_intrinsic_width(f32::INFINITY), 30.0);
+        // The rectangle is drawn at the size it was given, not the size it asked for.
+        let mut renderer = RecordingRenderer::new();
+        renderer
+            .render_widget(&mut sized, area(0.0, 0.0, 100.0, 100.0))
+            .unwrap();
+        assert_eq!(renderer.draws()[0].bounds, area(0.0, 0.0, 30.0, 10.0));
+
+        let mut constrained = ConstrainedBox::new(
+            BoxConstraints::new(Size2D::new(20.0, 0.0), Size2D::new(50.0, 5.0)),
+            rect(10.0, 10.0),
+        );
+        assert_eq!(
+            constrained.layout(loose(100.0, 100.0)),
+            Size2D::new(20.0, 5.0)
+        );
+        assert_eq!(constrained.max_intrinsic_height(f32::INFINITY), 5.0);
+    }
+
+    #[test]
+    fn aspect_ratio_fits_its_constraints() {
+        let mut ratio = AspectRatio::new(2.0, rect(1000.0, 1000.0)).unwrap();
+        assert_eq!(ratio.layout(loose(100.0, 100.0)), Size2D::new(100.0, 50.0));
+        assert_eq!(ratio.layout(loose(100.0, 20.0)), Size2D::new(40.0, 20.0));
+        let wide = BoxConstraints::new(Size2D::zero(), Size2D::new(f32::INFINITY, 30.0));
+        assert_eq!(ratio.layout(wide), Size2D::new(60.0, 30.0));
+        assert_eq!(ratio.min_intrinsic_width(10.0), 20.0);
+    }
+
+    #[test]
+    fn aspect_ratios_must_be_positive() {
+        assert!(AspectRatio::new(0.0, rect(10.0, 10.0)).is_none());
+        assert!(AspectRatio::new(f32::NAN, rect(10.0, 10.0)).is_none());
+        let mut ratio = AspectRatio::new(2.0, rect(10.0, 10.0)).unwrap();
+        assert!(!ratio.set_ratio(-1.0));
+        assert_eq!(ratio.ratio(), 2.0);
+        assert!(ratio.set_ratio(0.5));
+        assert_eq!(ratio.layout(loose(100.0, 100.0)), Size2D::new(50.0, 100.0));
+    }
+
+    #[test]
+    fn expanded_children_share_free_space() {
+        let mut row = Row::new()
+            .with_expanded_child(Expanded::with_flex(1.0, rect(100.0, 10.0)))
+            .with_expanded_child(Expanded::with_flex(3.0, rect(100.0, 10.0)));
+        row.layout(loose(200.0, 10.0));
+        assert_eq!(
+            row.child_bounds(),
+            [area(0.0, 0.0, 50.0, 10.0), area(50.0, 0.0, 200.0, 10.0)]
+        );
+    }
+
+    #[test]
+    fn stacks_overlay_positioned_children() {
+        let mut stack = Stack::new()
+            .with_alignment(Alignment::Center)
+            .with_child(rect(40.0, 20.0))
+            .with_child(rect(10.0, 10.0))
+            .with_positioned_child(
+                Positioned {
+                    right: Some(0.0),
+                    bottom: Some(0.0),
+                    ..Default::default()
+                },
+                rect(5.0, 5.0),
+            )
+            .with_positioned_child(Positioned::fill(EdgeInsets::all(2.0)), rect(100.0, 100.0));
+        assert_eq!(stack.layout(loose(100.0, 100.0)), Size2D::new(40.0, 20.0));
+        assert_eq!(
+            stack.child_bounds(),
+            [
+                area(0.0, 0.0, 40.0, 20.0),
+                area(15.0, 5.0, 25.0, 15.0),
+                area(35.0, 15.0, 40.0, 20.0),
+                area(2.0, 2.0, 38.0, 18.0),
+            ]
+        );
+        assert_eq!(stack.max_intrinsic_width(f32::INFINITY), 40.0);
+    }
+
+    /// Counts updates and accepts focus.
+    #[derive(Default)]
+    struct Counter(usize);
+    impl Widget for Counter {
+        fn render<Renderer: crate::rendering::Renderer>(
+            &mut self,
+            _canvas: Canvas<'_, Renderer>,
+        ) -> Result<(), RenderError> {
+            Ok(())
+        }
+        fn update(&mut self, _input: &Input) -> bool {
+            self.0 += 1;
+            true
+        }
+        fn focus(&mut self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn wrappers_forward_updates_and_focus() {
+        let input = Input::new(area(0.0, 0.0, 10.0, 10.0));
+        let mut widget = Padding::new(
+            EdgeInsets::all(1.0),
+            Center::new(SizedBox::new(
+                None,
+                None,
+                AspectRatio::new(1.0, Counter::default()).unwrap(),
+            )),
+        );
+        assert!(widget.update(&input));
+        assert!(widget.focus());
+        assert_eq!(widget.child.0.child.child.child.0, 1);
+
+        let mut stack = Stack::new()
+            .with_child(Counter::default())
+            .with_child(Counter::default());
+        assert!(stack.update(&input));
+        assert!(stack.children.iter().all(|child| child.widget.0 == 1));
+    }
+}
@@ -1,13 +1,18 @@
+use std::time::Instant;
+
 use crate::{
     input::Input,
-    math::{Box2D, Point2D, Size2D},
-    rendering::Canvas,
-    widgets::Widget,
+    math::{to_logical, to_physical, Box2D, PhysicalSize2D, Point2D, ScaleFactor, Size2D},
+    profiler::{FrameStats, Profiler},
+    rendering::{Canvas, RenderError, RendererConfig},
+    theme::{Theme, ThemeHandle},
+    widgets::{BoxConstraints, Widget},
 };
-use log::{debug, trace};
+use log::{debug, error, trace};
 use winit::{
     dpi::{LogicalSize, PhysicalSize},
     event::{Event, KeyboardInput, WindowEvent},
+    event_loop::EventLoopProxy,
 };
 
 pub struct Window<Renderer: crate::rendering::Renderer, Root: Widget> {
@@ -16,23 +21,49 @@ pub struct Window<Renderer: crate::rendering::Renderer, Root: Widget> {
     input: Input,
     renderer: Renderer,
     root: Root,
+    profiler: Profiler,
+    theme: ThemeHandle,
+    frame: FrameStats,
+    /// The size the root was last laid out at.
+    size: Size2D,
+    /// Lay out and render the root even if it didn't change, e.g. after a scale factor change.
+    needs_layout: bool,
 }
 impl<Renderer: crate::rendering::Renderer, Root: Widget> Window<Renderer, Root> {
-    pub fn new(title: &str, mut root: Root) -> Self {
+    /// Creates a window for `root`, failing if the renderer can't be set up.
+    ///
+    /// Use a `FallbackRenderer` to fall back to another renderer when the primary one fails.
+    pub fn new(title: &str, root: Root) -> Result<Self, RenderError> {
+        Self::with_config(title, root, RendererConfig::default())
+    }
+    /// Creates a window for `root`, setting up the renderer with `config`.
+    pub fn with_config(
+        title: &str,
+        mut root: Root,
+        config: RendererConfig,
+    ) -> Result<Self, RenderError> {
         let event_loop = winit::event_loop::EventLoop::new();
-        let size = root
-            .size(Size2D::new(800.0, 600.0))
-            .max(Size2D::new(1.0, 1.0));
-        let window = winit::window::WindowBuilder::new()
-            .with_title(title)
-            .with_inner_size(LogicalSize::new(size.width, size.height))
-            .build(&event_loop)
-            .unwrap();
-        let mut renderer = Renderer::new(&window);
+        let mut builder = winit::window::WindowBuilder::new().with_title(title);
+        if let Some(size) = intrinsic_size(&root) {
+            builder = builder.with_inner_size(LogicalSize::new(size.width, size.height));
+        }
+        let window = builder.build(&event_loop).unwrap();
+        let size = root.layout(BoxConstraints::loose(
+            inner_size(&window).max(Size2D::new(1.0, 1.0)),
+        ));
+        let mut renderer = Renderer::new(&window, &config)?;
+        let profiler = Profiler::new();
+        profiler.set_waker(Waker(Some(event_loop.create_proxy())));
+        let theme = ThemeHandle::new();
+        theme.set_waker(Waker(Some(event_loop.create_proxy())));
+        if let Some(mode) = system_theme(&window) {
+            theme.system_changed(mode);
+        }
         let mut input = Input::new(Box2D::new(
             Point2D::new(0.0, 0.0),
             Point2D::new(size.width, size.height),
         ));
+        input.set_theme(theme.take_changed().unwrap_or_default());
         input.update();
         root.update(&input);
         root.render(Canvas::new(
@@ -41,15 +72,41 @@ impl<Renderer: crate::rendering::Renderer, Root: Widget> Window<Renderer, Root>
                 Point2D::new(0.0, 0.0),
                 Point2D::new(size.width, size.height),
             ),
-        ));
-        renderer.render();
-        Self {
+            scale_factor(&window),
+            input.theme(),
+        ))?;
+        renderer.render()?;
+        Ok(Self {
             window,
             event_loop,
             input,
             renderer,
             root,
-        }
+            profiler,
+            theme,
+            frame: FrameStats::default(),
+            size,
+            needs_layout: false,
+        })
+    }
+    /// The number of physical pixels per logical pixel on the window's current monitor.
+    pub fn scale_factor(&self) -> ScaleFactor {
+        scale_factor(&self.window)
+    }
+    /// The size of the window's client area, in logical pixels.
+    pub fn inner_size(&self) -> Size2D {
+        inner_size(&self.window)
+    }
+    /// A handle to the timings of the frames this window renders.
+    pub fn profiler(&self) -> Profiler {
+        self.profiler.clone()
+    }
+    /// A handle to the window's theme, for switching it at runtime.
+    ///
+    /// By default the window uses the built-in light or dark theme matching the system's
+    /// preference, and switches when the system's preference changes.
+    pub fn theme(&self) -> ThemeHandle {
+        self.theme.clone()
     }
     pub fn run(mut self) -> !
     where
@@ -64,6 +121,42 @@ impl<Renderer: crate::rendering::Renderer, Root: Widget> Window<Renderer, Root>
                     event: WindowEvent::CloseRequested,
                     ..
                 } => *control_flow = winit::event_loop::ControlFlow::Exit,
+                Event::WindowEvent {
+                    event: WindowEvent::Resized(size),
+                    ..
+                } => {
+                    // Minimized windows report a size of zero. They are laid out again once
+                    // they're restored, which reports their real size.
+                    if !physical_size(size).is_empty() {
+                        self.renderer
+                            .resize(physical_size(size), scale_factor(&self.window));
+                        self.needs_layout = true;
+                    }
+                }
+                Event::WindowEvent {
+                    event:
+                        WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size,
+                        },
+                    ..
+                } => {
+                    let scale_factor = ScaleFactor::new(scale_factor as f32);
+                    debug!("Scale factor changed to {:?}", scale_factor);
+                    if !physical_size(*new_inner_size).is_empty() {
+                        self.renderer
+                            .resize(physical_size(*new_inner_size), scale_factor);
+                    }
+                    self.root.scale_factor_changed(scale_factor);
+                    self.needs_layout = true;
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::ThemeChanged(theme),
+                    ..
+                } => {
+                    debug!("System theme changed to {:?}", theme);
+                    self.theme.system_changed(theme.into());
+                }
                 Event::WindowEvent {
                     event:
                         WindowEvent::KeyboardInput {
@@ -104,41 +197,87 @@ impl<Renderer: crate::rendering::Renderer, Root: Widget> Window<Renderer, Root>
                         .process_mouse_move(Point2D::new(position.x, position.y));
                 }
                 Event::MainEventsCleared => {
+                    if let Some(theme) = self.theme.take_changed() {
+                        debug!("Switching to {:?} theme", theme.mode);
+                        self.root.theme_changed(&theme);
+                        self.input.set_theme(theme);
+                        self.needs_layout = true;
+                    }
                     self.input.update();
-                    if self.root.update(&self.input) {
+                    let start = Instant::now();
+                    let changed = self.root.update(&self.input);
+                    self.frame.update = start.elapsed();
+                    let needs_layout = std::mem::take(&mut self.needs_layout) || changed;
+                    if needs_layout && is_minimized(&self.window) {
+                        // Laying out now would shrink the window to fit a zero size.
+                        self.needs_layout = true;
+                    } else if needs_layout {
                         debug!("Updating");
-                        let window_size = self
-                            .window
-                            .inner_size()
-                            .to_logical(self.window.scale_factor());
-                        let size = self.root.size(
-                            Size2D::new(window_size.width, window_size.height)
-                                .max(Size2D::new(1.0, 1.0)),
-                        );
-                        self.root.render(Canvas::new(
-                            &mut self.renderer,
-                            Box2D::new(
-                                Point2D::new(0.0, 0.0),
-                                Point2D::new(size.width, size.height),
-                            ),
+                        let scale_factor = scale_factor(&self.window);
+                        let start = Instant::now();
+                        let size = self.root.layout(BoxConstraints::loose(
+                            inner_size(&self.window).max(Size2D::new(1.0, 1.0)),
                         ));
-                        if LogicalSize::new(size.width, size.height) != window_size {
+                        self.frame.layout = start.elapsed();
+                        self.size = size;
+                        let physical = to_physical(size, scale_factor);
+                        if physical != physical_size(self.window.inner_size()) {
                             debug!("Resizing window to: {:?}", size);
                             self.input.set_bounds(Box2D::new(
                                 Point2D::new(0.0, 0.0),
                                 Point2D::new(size.width, size.height),
                             ));
                             self.window
-                                .set_inner_size(PhysicalSize::new(size.width, size.height));
-                            self.renderer
-                                .resize(size, self.window.scale_factor() as f32);
+                                .set_inner_size(LogicalSize::new(size.width, size.height));
+                            self.renderer.resize(physical, scale_factor);
                         }
                         self.window.request_redraw();
                     }
                 }
+                // The root is drawn on every redraw, including ones the system asks for, since
+                // each frame is cleared before it's drawn.
+                Event::RedrawRequested(_) if is_minimized(&self.window) => {}
                 Event::RedrawRequested(_) => {
-                    self.renderer.render();
+                    let scale_factor = scale_factor(&self.window);
+                    let bounds = Box2D::new(
+                        Point2D::new(0.0, 0.0),
+                        Point2D::new(self.size.width, self.size.height),
+                    );
+                    let start = Instant::now();
+                    if let Err(err) = self.root.render(Canvas::new(
+                        &mut self.renderer,
+                        bounds,
+                        scale_factor,
+                        self.input.theme(),
+                    )) {
+                        error!("Failed to render root widget: {err}");
+                    }
+                    if self.profiler.overlay() {
+                        if let Err(err) = self.profiler.draw_overlay(&mut Canvas::new(
+                            &mut self.renderer,
+                            bounds,
+                            scale_factor,
+                            self.input.theme(),
+                        )) {
+                            error!("Failed to render profiler overlay: {err}");
+                        }
+                    }
+                    self.frame.render = start.elapsed();
+                    let start = Instant::now();
+                    // Renderers recover from what they can themselves, such as a lost device,
+                    // so an error here means rendering can't continue.
+                    if let Err(err) = self.renderer.render() {
+                        error!("Failed to render frame: {err}");
+                        *control_flow = winit::event_loop::ControlFlow::ExitWithCode(1);
+                    }
+                    self.frame.submit = start.elapsed();
+                    self.frame.renderer = self.renderer.stats();
+                    trace!("Frame stats: {:?}", self.frame);
+                    self.profiler.record(std::mem::take(&mut self.frame));
                 }
+                // Sent by a handle after a change that needs drawing, such as the theme being
+                // switched or the profiler overlay being toggled.
+                Event::UserEvent(()) => self.window.request_redraw(),
                 _ => {
                     trace!("Ignored event: {:?}", event);
                 }
@@ -147,9 +286,60 @@ impl<Renderer: crate::rendering::Renderer, Root: Widget> Window<Renderer, Root>
     }
 }
 
+/// Wakes a window's event loop, so changes made through a handle while it waits for events are
+/// drawn. Does nothing if the handle doesn't belong to a window.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Waker(Option<EventLoopProxy<()>>);
+impl Waker {
+    pub(crate) fn wake(&self) {
+        if let Some(proxy) = &self.0 {
+            // The loop has exited if this fails, so there's nothing to draw.
+            let _ = proxy.send_event(());
+        }
+    }
+}
+
+fn physical_size(size: PhysicalSize<u32>) -> PhysicalSize2D {
+    PhysicalSize2D::new(size.width, size.height)
+}
+/// Whether the window has no area to draw into, as when it's minimized.
+fn is_minimized(window: &winit::window::Window) -> bool {
+    physical_size(window.inner_size()).is_empty()
+}
+fn scale_factor(window: &winit::window::Window) -> ScaleFactor {
+    ScaleFactor::new(window.scale_factor() as f32)
+}
+fn inner_size(window: &winit::window::Window) -> Size2D {
+    to_logical(physical_size(window.inner_size()), scale_factor(window))
+}
+/// The size `root` would like to start at, if it has a non-empty intrinsic size.
+fn intrinsic_size(root: &impl Widget) -> Option<Size2D> {
+    let width = root.max_intrinsic_width(f32::INFINITY);
+    let height = root.max_intrinsic_height(width);
+    let size = Size2D::new(width, height);
+    (size.width > 0.0 && size.height > 0.0 && size.is_finite()).then_some(size)
+}
+/// The system's light or dark preference, where winit can query it before the first
+/// `ThemeChanged` event.
+fn system_theme(window: &winit::window::Window) -> Option<crate::theme::ThemeMode> {
+    #[cfg(target_os = "windows")]
+    {
+        use winit::platform::windows::WindowExtWindows;
+        Some(window.theme().into())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = window;
+        None
+    }
+}
+
 #[derive(Default)]
 pub struct WindowBuilder {
     window_builder: winit::window::WindowBuilder,
+    renderer_config: RendererConfig,
+    transparent: bool,
+    theme: Option<Theme>,
 }
 impl WindowBuilder {
     pub fn new() -> Self {
@@ -171,8 +361,10 @@ impl WindowBuilder {
         self.window_builder = self.window_builder.with_decorations(decorations);
         self
     }
+    /// Makes regions the root widget doesn't draw show what's behind the window.
     pub fn with_transparent(mut self, transparent: bool) -> Self {
         self.window_builder = self.window_builder.with_transparent(transparent);
+        self.transparent = transparent;
         self
     }
     pub fn with_always_on_top(mut self, always_on_top: bool) -> Self {
@@ -203,15 +395,42 @@ impl WindowBuilder {
         self.window_builder = self.window_builder.with_max_inner_size(size);
         self
     }
+    pub fn with_renderer_config(mut self, config: RendererConfig) -> Self {
+        self.renderer_config = config;
+        self
+    }
+    /// Starts with `theme` instead of following the system's light or dark preference.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
     pub fn build<Renderer: crate::rendering::Renderer, Root: Widget>(
         self,
         root: Root,
-    ) -> Window<Renderer, Root> {
+    ) -> Result<Window<Renderer, Root>, RenderError> {
         let event_loop = winit::event_loop::EventLoop::new();
         let window = self.window_builder.build(&event_loop).unwrap();
-        let renderer = Renderer::new(&window);
-        let size = window.inner_size().to_logical(window.scale_factor());
-        Window {
+        let renderer = Renderer::new(
+            &window,
+            &RendererConfig {
+                transparent: self.transparent || self.renderer_config.transparent,
+                ..self.renderer_config
+            },
+        )?;
+        let size = inner_size(&window);
+        let profiler = Profiler::new();
+        profiler.set_waker(Waker(Some(event_loop.create_proxy())));
+        let theme = ThemeHandle::new();
+        theme.set_waker(Waker(Some(event_loop.create_proxy())));
+        match self.theme {
+            Some(initial) => theme.set_theme(initial),
+            None => {
+                if let Some(mode) = system_theme(&window) {
+                    theme.system_changed(mode);
+                }
+            }
+        }
+        Ok(Window {
             window,
             event_loop,
             input: Input::new(Box2D::new(
@@ -220,6 +439,11 @@ impl WindowBuilder {
             )),
             renderer,
             root,
-        }
+            profiler,
+            theme,
+            frame: FrameStats::default(),
+            size,
+            needs_layout: true,
+        })
     }
 }